@@ -21,11 +21,19 @@ class MessageMark {
   MarkType markType;
   String name;
   int id;
+  String? channelPolicy;
+  String? broadcastCapacity;
+  bool isWatch;
+  bool isSticky;
   MessageMark(
     this.markType,
     this.name,
-    this.id,
-  );
+    this.id, {
+    this.channelPolicy,
+    this.broadcastCapacity,
+    this.isWatch = false,
+    this.isSticky = false,
+  });
 }
 
 Future<void> generateMessageCode({
@@ -62,6 +70,7 @@ Future<void> generateMessageCode({
     protoPath,
     resourcesInFolders,
   );
+  checkMergedSignalNames(markedMessagesAll);
   fillingBar.increment();
 
   // Include `package` statement in `.proto` files.
@@ -291,10 +300,14 @@ import 'package:rinf/rinf.dart';
 
 use prost::Message;
 use rinf::{
-    debug_print, send_rust_signal, signal_channel,
-    DartSignal, SignalReceiver, SignalSender,
+    broadcast_signal_channel, debug_print, register_reset, send_rust_signal,
+    signal_channel, signal_channel_with, sticky_signal_channel, watch_channel,
+    ChannelPolicy, DartSignal, SignalReceiver, SignalSender, WatchReceiver,
+    WatchSender, MERGED_SIGNAL_ID,
 };
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::LazyLock;
+use std::time::Duration;
 
 ''',
           atFront: true,
@@ -308,18 +321,42 @@ use std::sync::LazyLock;
         final snakeName = pascalToSnake(messageName);
         if (markType == MarkType.dartSignal ||
             markType == MarkType.dartSignalBinary) {
+          final channelPolicy = markedMessage.channelPolicy;
+          final broadcastCapacity = markedMessage.broadcastCapacity;
+          var channelInit = 'signal_channel()';
+          var senderType = 'SignalSender';
+          var receiverType = 'SignalReceiver';
+          if (markedMessage.isWatch) {
+            channelInit = 'watch_channel()';
+            senderType = 'WatchSender';
+            receiverType = 'WatchReceiver';
+          } else if (markedMessage.isSticky) {
+            final policy = channelPolicy ?? 'Unbounded';
+            channelInit = 'sticky_signal_channel(ChannelPolicy::$policy)';
+          } else if (channelPolicy != null) {
+            channelInit = 'signal_channel_with(ChannelPolicy::$channelPolicy)';
+          } else if (broadcastCapacity != null) {
+            channelInit = 'broadcast_signal_channel($broadcastCapacity)';
+          }
           await insertTextToFile(
             rustPath,
             '''
 type ${messageName}Channel = LazyLock<(
-    SignalSender<DartSignal<${normalizePascal(messageName)}>>,
-    SignalReceiver<DartSignal<${normalizePascal(messageName)}>>,
+    $senderType<DartSignal<${normalizePascal(messageName)}>>,
+    $receiverType<DartSignal<${normalizePascal(messageName)}>>,
 )>;
 pub static ${snakeName.toUpperCase()}_CHANNEL: ${messageName}Channel =
-    LazyLock::new(signal_channel);
+    LazyLock::new(|| {
+        let channel = $channelInit;
+        channel.0.register(${markedMessage.id}, "$messageName");
+        channel
+    });
+pub static ${snakeName.toUpperCase()}_RECEIVING: AtomicBool =
+    AtomicBool::new(false);
 
 impl ${normalizePascal(messageName)} {
-    pub fn get_dart_signal_receiver() -> SignalReceiver<DartSignal<Self>> {
+    pub fn get_dart_signal_receiver() -> $receiverType<DartSignal<Self>> {
+        ${snakeName.toUpperCase()}_RECEIVING.store(true, Ordering::SeqCst);
         ${snakeName.toUpperCase()}_CHANNEL.1.clone()
     }
 }
@@ -414,6 +451,61 @@ impl ${normalizePascal(messageName)} {
           );
         }
       }
+      final dartSignals = markedMessages
+          .where((markedMessage) =>
+              markedMessage.markType == MarkType.dartSignal ||
+              markedMessage.markType == MarkType.dartSignalBinary)
+          .toList();
+      if (dartSignals.isNotEmpty) {
+        final protoName = '${subPath.substring(1)}$filename.proto';
+        final enumName = mergedSignalName(subPath, filename);
+        final snakeEnumName = pascalToSnake(enumName);
+        final upperName = snakeEnumName.toUpperCase();
+        final variants = dartSignals
+            .map((markedMessage) => normalizePascal(markedMessage.name))
+            .map((name) => '    $name(DartSignal<$name>),')
+            .join('\n');
+        final flagResets = dartSignals
+            .map((markedMessage) => pascalToSnake(markedMessage.name))
+            .map((snakeName) => '    ${snakeName.toUpperCase()}_RECEIVING'
+                '.store(false, Ordering::SeqCst);')
+            .join('\n');
+        await insertTextToFile(
+          rustPath,
+          '''
+/// Every Dart signal defined in `$protoName`.
+pub enum $enumName {
+$variants
+}
+type ${enumName}Channel = LazyLock<(
+    SignalSender<$enumName>,
+    SignalReceiver<$enumName>,
+)>;
+pub static ${upperName}_CHANNEL: ${enumName}Channel =
+    LazyLock::new(|| {
+        let channel = signal_channel();
+        channel.0.register(MERGED_SIGNAL_ID, "$enumName");
+        register_reset(reset_${snakeEnumName}_flags);
+        channel
+    });
+pub static ${upperName}_RECEIVING: AtomicBool = AtomicBool::new(false);
+
+/// Makes signals of `$protoName` go to individual channels again,
+/// until a merged receiver is obtained in the new Dart session.
+fn reset_${snakeEnumName}_flags() {
+    ${upperName}_RECEIVING.store(false, Ordering::SeqCst);
+$flagResets
+}
+
+impl $enumName {
+    pub fn get_dart_signal_receiver() -> SignalReceiver<Self> {
+        ${upperName}_RECEIVING.store(true, Ordering::SeqCst);
+        ${upperName}_CHANNEL.1.clone()
+    }
+}
+''',
+        );
+      }
     }
   }
 
@@ -427,6 +519,7 @@ use super::*;
 use prost::Message;
 use rinf::{DartSignal, RinfError};
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::LazyLock;
 
 type Handler = dyn Fn(&[u8], &[u8]) -> Result<(), RinfError> + Send + Sync;
@@ -438,6 +531,7 @@ static DART_SIGNAL_HANDLERS: LazyLock<DartSignalHandlers> = LazyLock::new(|| {
     final subpath = entry.key;
     final files = entry.value;
     for (final entry in files.entries) {
+      final filename = entry.key;
       final markedMessages = entry.value;
       for (final markedMessage in markedMessages) {
         final markType = markedMessage.markType;
@@ -445,6 +539,8 @@ static DART_SIGNAL_HANDLERS: LazyLock<DartSignalHandlers> = LazyLock::new(|| {
             markType == MarkType.dartSignalBinary) {
           final messageName = markedMessage.name;
           final snakeName = pascalToSnake(messageName);
+          final enumName = mergedSignalName(subpath, filename);
+          final upperName = pascalToSnake(enumName).toUpperCase();
           var modulePath = subpath.replaceAll('/', '::');
           modulePath = modulePath == '::' ? '' : modulePath;
           rustReceiveScript += '''
@@ -458,7 +554,20 @@ hash_map.insert(
             message,
             binary: binary.to_vec(),
         };
-        ${snakeName.toUpperCase()}_CHANNEL.0.send(dart_signal);
+        let is_merged = ${upperName}_RECEIVING.load(Ordering::SeqCst);
+        if is_merged {
+            let merged = ${enumName}::${normalizePascal(messageName)}(
+                dart_signal.clone(),
+            );
+            ${upperName}_CHANNEL.0.send(merged);
+        }
+        // Once merged, only deliver to receivers that were obtained
+        // so that unused channels don't pile up signals
+        let is_received =
+            ${snakeName.toUpperCase()}_RECEIVING.load(Ordering::SeqCst);
+        if !is_merged || is_received {
+            ${snakeName.toUpperCase()}_CHANNEL.0.send(dart_signal);
+        }
         Ok(())
     }),
 );
@@ -712,6 +821,9 @@ Future<Map<String, Map<String, List<MessageMark>>>> analyzeMarkedMessages(
       final content = await protoFile.readAsString();
       final regExp = RegExp(r'{[^}]*}');
       final attrExp = RegExp(r'(?<=\[RUST-ATTRIBUTE\().*(?=\)\])');
+      final policyExp = RegExp(r'(?<=\[DART-SIGNAL-POLICY\().*(?=\)\])');
+      final broadcastExp =
+          RegExp(r'(?<=\[DART-SIGNAL-BROADCAST\().*(?=\)\])');
 
       // Remove all { ... } blocks from the string
       final contentWithoutBlocks = content.replaceAll(regExp, ';');
@@ -733,6 +845,33 @@ Future<Map<String, Map<String, List<MessageMark>>>> analyzeMarkedMessages(
           continue;
         }
 
+        // Find [DART-SIGNAL-POLICY(...)] and [DART-SIGNAL-BROADCAST(...)]
+        final channelPolicy = policyExp.stringMatch(statement);
+        final broadcastCapacity = broadcastExp.stringMatch(statement);
+        if (channelPolicy != null && broadcastCapacity != null) {
+          throw Exception(
+            '`DART-SIGNAL-POLICY` and `DART-SIGNAL-BROADCAST` '
+            'cannot be used together',
+          );
+        }
+
+        // Find [DART-SIGNAL-WATCH]
+        final isWatch = statement.contains('[DART-SIGNAL-WATCH]');
+        if (isWatch && (channelPolicy != null || broadcastCapacity != null)) {
+          throw Exception(
+            '`DART-SIGNAL-WATCH` cannot be used with other channel marks',
+          );
+        }
+
+        // Find [DART-SIGNAL-STICKY]
+        final isSticky = statement.contains('[DART-SIGNAL-STICKY]');
+        if (isSticky && (isWatch || broadcastCapacity != null)) {
+          throw Exception(
+            '`DART-SIGNAL-STICKY` cannot be used with '
+            '`DART-SIGNAL-WATCH` or `DART-SIGNAL-BROADCAST`',
+          );
+        }
+
         // Find [DART-SIGNAL]
         if (statement.contains('[DART-SIGNAL]')) {
           if (statement.contains('DART-SIGNAL-BINARY')) {
@@ -744,13 +883,28 @@ Future<Map<String, Map<String, List<MessageMark>>>> analyzeMarkedMessages(
             MarkType.dartSignal,
             messageName,
             messageId,
+            channelPolicy: channelPolicy,
+            broadcastCapacity: broadcastCapacity,
+            isWatch: isWatch,
+            isSticky: isSticky,
           ));
         } else if (statement.contains('[DART-SIGNAL-BINARY]')) {
           messageMarks[subPath]![filename]!.add(MessageMark(
             MarkType.dartSignalBinary,
             messageName,
             messageId,
+            channelPolicy: channelPolicy,
+            broadcastCapacity: broadcastCapacity,
+            isWatch: isWatch,
+            isSticky: isSticky,
           ));
+        } else if (channelPolicy != null ||
+            broadcastCapacity != null ||
+            isWatch ||
+            isSticky) {
+          throw Exception(
+            'Channel marks require `DART-SIGNAL` or `DART-SIGNAL-BINARY`',
+          );
         }
 
         // Find [RUST-SIGNAL]
@@ -791,6 +945,72 @@ Future<Map<String, Map<String, List<MessageMark>>>> analyzeMarkedMessages(
   return messageMarks;
 }
 
+/// Names the enum that merges the Dart signals of a `.proto` file
+/// after its folder and file name,
+/// so that files with the same name in different folders don't clash.
+String mergedSignalName(String subPath, String filename) {
+  final parts = subPath.split('/').where((part) => part.isNotEmpty).toList();
+  parts.add(filename);
+  final pascalName = parts.map(snakeToPascal).join();
+  return '${normalizePascal(pascalName)}DartSignal';
+}
+
+/// Makes sure that every merged enum has a unique name,
+/// which doesn't clash with the name of a marked message either.
+/// Generated items are re-exported from every module,
+/// so a clash would make the Rust code fail to compile.
+void checkMergedSignalNames(
+  Map<String, Map<String, List<MessageMark>>> markedMessagesAll,
+) {
+  // Names are compared in snake case,
+  // as statics are named after them as well.
+  final owners = <String, String>{};
+  void claim(String name, String owner) {
+    final key = pascalToSnake(name);
+    final existing = owners[key];
+    if (existing != null) {
+      throw Exception(
+        'The generated name `$name` of $owner clashes with $existing. '
+        'Rename one of the files or messages.',
+      );
+    }
+    owners[key] = owner;
+  }
+
+  for (final entry in markedMessagesAll.entries) {
+    final subPath = entry.key;
+    for (final entry in entry.value.entries) {
+      final protoName = '${subPath.substring(1)}${entry.key}.proto';
+      final markedMessages = entry.value;
+      for (final markedMessage in markedMessages) {
+        if (markedMessage.markType == MarkType.dartSignal ||
+            markedMessage.markType == MarkType.dartSignalBinary) {
+          claim(
+            normalizePascal(markedMessage.name),
+            'message `${markedMessage.name}` in `$protoName`',
+          );
+        }
+      }
+    }
+  }
+  for (final entry in markedMessagesAll.entries) {
+    final subPath = entry.key;
+    for (final entry in entry.value.entries) {
+      final filename = entry.key;
+      final protoName = '${subPath.substring(1)}$filename.proto';
+      final hasDartSignals = entry.value.any((markedMessage) =>
+          markedMessage.markType == MarkType.dartSignal ||
+          markedMessage.markType == MarkType.dartSignalBinary);
+      if (hasDartSignals) {
+        claim(
+          mergedSignalName(subPath, filename),
+          'the merged enum of `$protoName`',
+        );
+      }
+    }
+  }
+}
+
 String pascalToCamel(String input) {
   if (input.isEmpty) {
     return input;
@@ -817,6 +1037,14 @@ String snakeToCamel(String input) {
   return camelCase;
 }
 
+String snakeToPascal(String input) {
+  final camelCase = snakeToCamel(input);
+  if (camelCase.isEmpty) {
+    return camelCase;
+  }
+  return camelCase[0].toUpperCase() + camelCase.substring(1);
+}
+
 /// Converts a string `HeLLLLLLLo` to `HeLlllllLo`,
 /// just like `protoc-gen-prost` does.
 String normalizePascal(String input) {
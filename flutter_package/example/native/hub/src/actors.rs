@@ -34,7 +34,7 @@ impl CountingActor {
         // button click signals from Dart.
         let receiver = SampleNumberInput::get_dart_signal_receiver();
         // Continuously listen for signals.
-        while let Some(dart_signal) = receiver.recv().await {
+        while let Ok(dart_signal) = receiver.recv().await {
             let letter = dart_signal.message.letter;
             debug_print!("{letter}");
             // Send a letter to the counting actor.
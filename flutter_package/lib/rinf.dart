@@ -5,8 +5,12 @@ import 'dart:typed_data';
 import 'src/exports.dart';
 
 export 'src/interface.dart' show RustSignal;
+export 'src/crash.dart'
+    show RustCrash, RustCrashAction, rustCrashStream, RustPanic, rustPanicStream;
 
 /// Starts the `main` function in Rust.
+/// App lifecycle changes and other system events
+/// are forwarded to Rust from then on.
 Future<void> initializeRust(
   AssignRustSignal assignRustSignal, {
   String? compiledLibPath,
@@ -16,12 +20,13 @@ Future<void> initializeRust(
   }
   await prepareInterfaceReal(assignRustSignal);
   startRustLogicReal();
+  observeSystemEvents();
 }
 
 /// Terminates all Rust tasks by dropping the async runtime.
 /// Calling this function before closing the Flutter app
 /// can prevent potential resource leaks.
-/// Please note that on the web, this function does not have any effect,
+/// On the web, this only notifies Rust that Dart has stopped,
 /// as tasks are managed by the JavaScript runtime, not Rust.
 void finalizeRust() async {
   stopRustLogicReal();
@@ -0,0 +1,124 @@
+import 'dart:async';
+import 'dart:convert';
+import 'dart:typed_data';
+
+/// A special message ID for crash reports from Rust.
+const rustCrashId = -3;
+
+/// What Rust did after its `main` function panicked.
+enum RustCrashAction {
+  /// The Rust logic stopped.
+  stop,
+
+  /// The `main` function will run again after a backoff.
+  restart,
+
+  /// The whole process is being aborted.
+  abort,
+}
+
+/// A report about a panic in the `main` function of Rust.
+class RustCrash {
+  /// The message given to `panic!`.
+  final String message;
+
+  /// How many times the Rust logic was restarted before this crash.
+  final int restarts;
+
+  /// What Rust does after this crash.
+  final RustCrashAction action;
+
+  /// How long Rust waits before restarting.
+  final Duration backoff;
+
+  const RustCrash({
+    required this.message,
+    required this.restarts,
+    required this.action,
+    required this.backoff,
+  });
+
+  @override
+  String toString() {
+    return 'RustCrash($message, restarts: $restarts, action: ${action.name})';
+  }
+}
+
+final _rustCrashController = StreamController<RustCrash>.broadcast();
+
+/// Crash reports of the Rust logic.
+/// Unlike Rust reports from `debug_print!`,
+/// these are also sent in release mode.
+Stream<RustCrash> get rustCrashStream => _rustCrashController.stream;
+
+/// Decodes a crash report from Rust and adds it to the stream.
+void reportRustCrash(Uint8List binary) {
+  final json = jsonDecode(utf8.decode(binary)) as Map<String, dynamic>;
+  final rustCrash = RustCrash(
+    message: json['message'] as String,
+    restarts: json['restarts'] as int,
+    action: RustCrashAction.values.byName(json['action'] as String),
+    backoff: Duration(milliseconds: json['backoffMillis'] as int),
+  );
+  print('Rust logic crashed: $rustCrash');
+  _rustCrashController.add(rustCrash);
+}
+
+/// A special message ID for panic reports from Rust.
+const rustPanicId = -4;
+
+/// A report about a panic anywhere in Rust,
+/// sent when panic reports are enabled with `rinf::set_panic_reports`.
+class RustPanic {
+  /// The message given to `panic!`.
+  final String message;
+
+  /// The source file where the panic occurred, if known.
+  final String? file;
+
+  /// The line in the source file, if known.
+  final int? line;
+
+  /// The column in the source file, if known.
+  final int? column;
+
+  /// The name of the thread that panicked, if it has one.
+  final String? thread;
+
+  /// A symbolicated backtrace,
+  /// included when the `show-backtrace` feature is enabled.
+  final String? backtrace;
+
+  const RustPanic({
+    required this.message,
+    this.file,
+    this.line,
+    this.column,
+    this.thread,
+    this.backtrace,
+  });
+
+  @override
+  String toString() {
+    return 'RustPanic($message, at $file:$line:$column, thread: $thread)';
+  }
+}
+
+final _rustPanicController = StreamController<RustPanic>.broadcast();
+
+/// Panic reports from Rust, including those in release mode.
+Stream<RustPanic> get rustPanicStream => _rustPanicController.stream;
+
+/// Decodes a panic report from Rust and adds it to the stream.
+void reportRustPanic(Uint8List binary) {
+  final json = jsonDecode(utf8.decode(binary)) as Map<String, dynamic>;
+  final rustPanic = RustPanic(
+    message: json['message'] as String,
+    file: json['file'] as String?,
+    line: json['line'] as int?,
+    column: json['column'] as int?,
+    thread: json['thread'] as String?,
+    backtrace: json['backtrace'] as String?,
+  );
+  _rustPanicController.add(rustPanic);
+}
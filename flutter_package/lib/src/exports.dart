@@ -1,2 +1,4 @@
 export 'interface_os.dart' if (dart.library.html) 'interface_web.dart';
 export 'interface.dart';
+export 'crash.dart';
+export 'system.dart';
@@ -4,6 +4,7 @@ import 'load_os.dart';
 import 'dart:async';
 import 'dart:isolate';
 import 'interface.dart';
+import 'crash.dart';
 import 'dart:convert';
 
 /// Sets the exact file path of the dynamic library
@@ -34,6 +35,14 @@ Future<void> prepareInterfaceReal(
       print(rustReport);
       return;
     }
+    if (messageId == rustCrashId) {
+      reportRustCrash(binary);
+      return;
+    }
+    if (messageId == rustPanicId) {
+      reportRustPanic(binary);
+      return;
+    }
     if (messageBytes == null) {
       // Rust will send null if the vector is empty.
       // Converting is needed on the Dart side.
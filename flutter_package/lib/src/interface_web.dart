@@ -7,6 +7,7 @@ import 'dart:typed_data';
 import 'dart:convert';
 import 'load_web.dart';
 import 'interface.dart';
+import 'crash.dart';
 
 /// Sets the path to the JavaScript module
 /// that needs to be loaded.
@@ -32,6 +33,10 @@ Future<void> prepareInterfaceReal(
       print(rustReport);
       return;
     }
+    if (messageId == rustPanicId) {
+      reportRustPanic(binary);
+      return;
+    }
     assignRustSignal(messageId, messageBytes, binary);
   }.jsify();
 }
@@ -44,7 +49,7 @@ void startRustLogicReal() {
 }
 
 void stopRustLogicReal() {
-  // Dummy function to match the structure of native platforms.
+  wasmBindingsObject.callMethod('stop_rust_logic_extern'.toJS);
 }
 
 void sendDartSignalReal(
@@ -0,0 +1,51 @@
+import 'dart:convert';
+import 'package:flutter/widgets.dart';
+import 'interface_os.dart' if (dart.library.html) 'interface_web.dart';
+
+/// A special message ID for system events sent to Rust.
+const systemEventId = -2;
+
+/// Forwards app lifecycle changes and other system events to Rust.
+class SystemEventObserver with WidgetsBindingObserver {
+  @override
+  void didChangeAppLifecycleState(AppLifecycleState state) {
+    sendSystemEvent(state.name);
+  }
+
+  @override
+  void didHaveMemoryPressure() {
+    sendSystemEvent('memoryPressure');
+  }
+
+  @override
+  void didChangeLocales(List<Locale>? locales) {
+    final languageTags = (locales ?? []).map(
+      (locale) => locale.toLanguageTag(),
+    );
+    sendSystemEvent('localeChanged', languageTags.join(','));
+  }
+}
+
+/// Sends a system event to Rust.
+/// The kind of event goes in the message bytes,
+/// and its payload goes in the binary.
+void sendSystemEvent(String kind, [String payload = '']) {
+  sendDartSignalReal(
+    systemEventId,
+    utf8.encode(kind),
+    utf8.encode(payload),
+  );
+}
+
+SystemEventObserver? _systemEventObserver;
+
+/// Starts forwarding system events to Rust.
+/// Calling this more than once has no additional effect.
+void observeSystemEvents() {
+  if (_systemEventObserver != null) {
+    return;
+  }
+  final observer = SystemEventObserver();
+  WidgetsFlutterBinding.ensureInitialized().addObserver(observer);
+  _systemEventObserver = observer;
+}
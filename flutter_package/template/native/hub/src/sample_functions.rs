@@ -8,7 +8,7 @@ pub async fn communicate() {
 
     // Get receivers that listen to Dart signals like below.
     let receiver = SmallText::get_dart_signal_receiver();
-    while let Some(dart_signal) = receiver.recv().await {
+    while let Ok(dart_signal) = receiver.recv().await {
         let message: SmallText = dart_signal.message;
         rinf::debug_print!("{message:?}");
     }
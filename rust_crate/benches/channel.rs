@@ -0,0 +1,242 @@
+//! Measures the throughput and latency of signal channels.
+//! Each measurement is compared against a queue guarded by a mutex,
+//! which is how signal channels used to store messages.
+
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion, Throughput,
+};
+use rinf::{signal_channel, SignalReceiver, SignalSender};
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+const MESSAGE_COUNT: usize = 10_000;
+const PRODUCER_COUNTS: [usize; 3] = [1, 2, 4];
+const CHANNEL_COUNTS: [usize; 3] = [1, 4, 8];
+
+/// A minimal channel that locks a mutex on every operation.
+struct MutexChannel<T> {
+    queue: Mutex<VecDeque<T>>,
+    condvar: Condvar,
+}
+
+impl<T> MutexChannel<T> {
+    fn new() -> Arc<Self> {
+        Arc::new(MutexChannel {
+            queue: Mutex::new(VecDeque::new()),
+            condvar: Condvar::new(),
+        })
+    }
+
+    fn send(&self, msg: T) {
+        let mut guard = match self.queue.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.push_back(msg);
+        self.condvar.notify_one();
+    }
+
+    fn recv_blocking(&self) -> T {
+        let mut guard = match self.queue.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        loop {
+            if let Some(msg) = guard.pop_front() {
+                return msg;
+            }
+            guard = match self.condvar.wait(guard) {
+                Ok(inner) => inner,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+    }
+}
+
+/// Sends messages from several threads at once
+/// and receives all of them on the current thread.
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(MESSAGE_COUNT as u64));
+    for producers in PRODUCER_COUNTS {
+        let per_producer = MESSAGE_COUNT / producers;
+        group.bench_with_input(
+            BenchmarkId::new("signal_channel", producers),
+            &producers,
+            |b, &producers| {
+                b.iter(|| {
+                    let (sender, receiver) = signal_channel::<usize>();
+                    let handles: Vec<_> = (0..producers)
+                        .map(|_| {
+                            let sender = sender.clone();
+                            thread::spawn(move || {
+                                for i in 0..per_producer {
+                                    sender.send(i);
+                                }
+                            })
+                        })
+                        .collect();
+                    for _ in 0..per_producer * producers {
+                        let _ = black_box(receiver.recv_blocking());
+                    }
+                    for handle in handles {
+                        let _ = handle.join();
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mutex_queue", producers),
+            &producers,
+            |b, &producers| {
+                b.iter(|| {
+                    let channel = MutexChannel::<usize>::new();
+                    let handles: Vec<_> = (0..producers)
+                        .map(|_| {
+                            let channel = channel.clone();
+                            thread::spawn(move || {
+                                for i in 0..per_producer {
+                                    channel.send(i);
+                                }
+                            })
+                        })
+                        .collect();
+                    for _ in 0..per_producer * producers {
+                        black_box(channel.recv_blocking());
+                    }
+                    for handle in handles {
+                        let _ = handle.join();
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Runs several independent channels at once,
+/// each with its own producer and receiving thread.
+/// Channels should not slow each other down,
+/// as they don't share any state.
+fn independent_channels(c: &mut Criterion) {
+    let mut group = c.benchmark_group("independent_channels");
+    for channels in CHANNEL_COUNTS {
+        let total = (MESSAGE_COUNT * channels) as u64;
+        group.throughput(Throughput::Elements(total));
+        group.bench_with_input(
+            BenchmarkId::new("signal_channel", channels),
+            &channels,
+            |b, &channels| {
+                b.iter(|| {
+                    let handles: Vec<_> = (0..channels)
+                        .flat_map(|_| {
+                            let (sender, receiver) = signal_channel::<usize>();
+                            let producer = thread::spawn(move || {
+                                for i in 0..MESSAGE_COUNT {
+                                    sender.send(i);
+                                }
+                            });
+                            let consumer = thread::spawn(move || {
+                                for _ in 0..MESSAGE_COUNT {
+                                    let _ = black_box(receiver.recv_blocking());
+                                }
+                            });
+                            [producer, consumer]
+                        })
+                        .collect();
+                    for handle in handles {
+                        let _ = handle.join();
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mutex_queue", channels),
+            &channels,
+            |b, &channels| {
+                b.iter(|| {
+                    let handles: Vec<_> = (0..channels)
+                        .flat_map(|_| {
+                            let channel = MutexChannel::<usize>::new();
+                            let producer = {
+                                let channel = channel.clone();
+                                thread::spawn(move || {
+                                    for i in 0..MESSAGE_COUNT {
+                                        channel.send(i);
+                                    }
+                                })
+                            };
+                            let consumer = thread::spawn(move || {
+                                for _ in 0..MESSAGE_COUNT {
+                                    black_box(channel.recv_blocking());
+                                }
+                            });
+                            [producer, consumer]
+                        })
+                        .collect();
+                    for handle in handles {
+                        let _ = handle.join();
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Sends a message to another thread and waits for it to come back.
+fn latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("latency");
+
+    let (ping_sender, ping_receiver) = signal_channel::<usize>();
+    let (pong_sender, pong_receiver) = signal_channel::<usize>();
+    let echo = spawn_echo(ping_receiver, pong_sender);
+    group.bench_function("signal_channel", |b| {
+        b.iter(|| {
+            ping_sender.send(black_box(1));
+            let _ = black_box(pong_receiver.recv_blocking());
+        });
+    });
+    // Dropping the last sender closes the channel and stops the echo thread
+    drop(ping_sender);
+    let _ = echo.join();
+
+    let ping = MutexChannel::<Option<usize>>::new();
+    let pong = MutexChannel::<usize>::new();
+    let echo = {
+        let ping = ping.clone();
+        let pong = pong.clone();
+        thread::spawn(move || {
+            while let Some(msg) = ping.recv_blocking() {
+                pong.send(msg);
+            }
+        })
+    };
+    group.bench_function("mutex_queue", |b| {
+        b.iter(|| {
+            ping.send(Some(black_box(1)));
+            black_box(pong.recv_blocking());
+        });
+    });
+    ping.send(None);
+    let _ = echo.join();
+
+    group.finish();
+}
+
+/// Sends every received message back until the channel is closed.
+fn spawn_echo(
+    receiver: SignalReceiver<usize>,
+    sender: SignalSender<usize>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        while let Ok(msg) = receiver.recv_blocking() {
+            sender.send(msg);
+        }
+    })
+}
+
+criterion_group!(benches, throughput, independent_channels, latency);
+criterion_main!(benches);
@@ -0,0 +1,329 @@
+use crate::error::RecvError;
+use crate::time::{sleep, Sleep};
+use std::future::Future;
+use std::mem::take;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// A source of messages that can be received one by one,
+/// such as a `SignalReceiver` or an adapter wrapping one.
+/// Adapters can be chained to transform the messages
+/// before they are received.
+/// Timers used by the adapters don't rely on any specific async runtime,
+/// so they work on the web as well.
+pub trait SignalSource {
+    /// The type of messages that are received.
+    type Item;
+
+    /// Checks if a message is available. If not, the task will be
+    /// woken up when the source might have one.
+    /// If the source cannot provide messages anymore,
+    /// this will return the reason.
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>>;
+
+    /// Asynchronously receives the next message from the source.
+    fn recv(
+        &mut self,
+    ) -> impl Future<Output = Result<Self::Item, RecvError>> + '_
+    where
+        Self: Sized,
+    {
+        RecvNext { source: self }
+    }
+
+    /// Transforms each message with the given function.
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> U,
+    {
+        Map { source: self, f }
+    }
+
+    /// Only lets messages that satisfy the given predicate through.
+    fn filter<F>(self, predicate: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Self::Item) -> bool,
+    {
+        Filter {
+            source: self,
+            predicate,
+        }
+    }
+
+    /// Waits until no message has arrived for the given duration,
+    /// and then provides only the most recent one.
+    /// This is useful for search boxes, where only
+    /// the input after the user stops typing matters.
+    fn debounce(self, delay: Duration) -> Debounce<Self>
+    where
+        Self: Sized,
+    {
+        Debounce {
+            source: self,
+            delay,
+            pending: None,
+            timer: None,
+            error: None,
+        }
+    }
+
+    /// Provides at most one message per interval.
+    /// The first message is provided immediately,
+    /// and the most recent message that arrived during the interval
+    /// is provided when the interval ends.
+    /// This is useful for sliders and resize events.
+    fn throttle(self, interval: Duration) -> Throttle<Self>
+    where
+        Self: Sized,
+    {
+        Throttle {
+            source: self,
+            interval,
+            pending: None,
+            window: None,
+            error: None,
+        }
+    }
+
+    /// Collects messages into batches. A batch is provided when it has
+    /// `max_len` messages, or when the given duration has passed
+    /// since its first message arrived.
+    fn chunks_timeout(
+        self,
+        max_len: usize,
+        timeout: Duration,
+    ) -> ChunksTimeout<Self>
+    where
+        Self: Sized,
+    {
+        ChunksTimeout {
+            source: self,
+            max_len: max_len.max(1),
+            timeout,
+            buffer: Vec::new(),
+            timer: None,
+            error: None,
+        }
+    }
+}
+
+/// A future that receives the next message from a `SignalSource`.
+struct RecvNext<'a, S> {
+    source: &'a mut S,
+}
+
+impl<S: SignalSource> Future for RecvNext<'_, S> {
+    type Output = Result<S::Item, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().source.poll_recv(cx)
+    }
+}
+
+/// Created by `SignalSource::map`.
+pub struct Map<S, F> {
+    source: S,
+    f: F,
+}
+
+impl<S, F, U> SignalSource for Map<S, F>
+where
+    S: SignalSource,
+    F: FnMut(S::Item) -> U,
+{
+    type Item = U;
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        self.source
+            .poll_recv(cx)
+            .map(|result| result.map(&mut self.f))
+    }
+}
+
+/// Created by `SignalSource::filter`.
+pub struct Filter<S, F> {
+    source: S,
+    predicate: F,
+}
+
+impl<S, F> SignalSource for Filter<S, F>
+where
+    S: SignalSource,
+    F: FnMut(&S::Item) -> bool,
+{
+    type Item = S::Item;
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        loop {
+            match self.source.poll_recv(cx) {
+                Poll::Ready(Ok(item)) => {
+                    if (self.predicate)(&item) {
+                        return Poll::Ready(Ok(item));
+                    }
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// Created by `SignalSource::debounce`.
+pub struct Debounce<S: SignalSource> {
+    source: S,
+    delay: Duration,
+    pending: Option<S::Item>, // The most recent message
+    timer: Option<Sleep>,     // Restarted on every message
+    error: Option<RecvError>, // Provided after the pending message
+}
+
+impl<S: SignalSource> SignalSource for Debounce<S> {
+    type Item = S::Item;
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        // Take all messages that have arrived so far
+        while self.error.is_none() {
+            match self.source.poll_recv(cx) {
+                Poll::Ready(Ok(item)) => {
+                    self.pending = Some(item);
+                    self.timer = Some(sleep(self.delay));
+                }
+                Poll::Ready(Err(error)) => self.error = Some(error),
+                Poll::Pending => break,
+            }
+        }
+
+        // Provide the pending message without waiting
+        // if no more messages will arrive
+        let is_quiet = match self.timer.as_mut() {
+            Some(timer) => Pin::new(timer).poll(cx).is_ready(),
+            None => false,
+        };
+        if is_quiet || self.error.is_some() {
+            self.timer = None;
+            if let Some(item) = self.pending.take() {
+                return Poll::Ready(Ok(item));
+            }
+        }
+        match self.error {
+            Some(error) => Poll::Ready(Err(error)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Created by `SignalSource::throttle`.
+pub struct Throttle<S: SignalSource> {
+    source: S,
+    interval: Duration,
+    pending: Option<S::Item>, // The most recent message during the interval
+    window: Option<Sleep>,    // Present while the interval lasts
+    error: Option<RecvError>, // Provided after the pending message
+}
+
+impl<S: SignalSource> SignalSource for Throttle<S> {
+    type Item = S::Item;
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        while self.error.is_none() {
+            match self.source.poll_recv(cx) {
+                Poll::Ready(Ok(item)) => {
+                    // Provide the message immediately
+                    // if the interval is not running
+                    if self.window.is_none() {
+                        self.window = Some(sleep(self.interval));
+                        return Poll::Ready(Ok(item));
+                    }
+                    self.pending = Some(item);
+                }
+                Poll::Ready(Err(error)) => self.error = Some(error),
+                Poll::Pending => break,
+            }
+        }
+
+        let has_ended = match self.window.as_mut() {
+            Some(window) => Pin::new(window).poll(cx).is_ready(),
+            None => true,
+        };
+        if has_ended || self.error.is_some() {
+            self.window = None;
+            if let Some(item) = self.pending.take() {
+                // Start another interval for the messages that follow
+                if self.error.is_none() {
+                    self.window = Some(sleep(self.interval));
+                }
+                return Poll::Ready(Ok(item));
+            }
+        }
+        match self.error {
+            Some(error) => Poll::Ready(Err(error)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Created by `SignalSource::chunks_timeout`.
+pub struct ChunksTimeout<S: SignalSource> {
+    source: S,
+    max_len: usize,
+    timeout: Duration,
+    buffer: Vec<S::Item>, // Messages in the current batch
+    timer: Option<Sleep>, // Started on the first message of a batch
+    error: Option<RecvError>, // Provided after the last batch
+}
+
+impl<S: SignalSource> SignalSource for ChunksTimeout<S> {
+    type Item = Vec<S::Item>;
+
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        while self.error.is_none() {
+            match self.source.poll_recv(cx) {
+                Poll::Ready(Ok(item)) => {
+                    if self.buffer.is_empty() {
+                        self.timer = Some(sleep(self.timeout));
+                    }
+                    self.buffer.push(item);
+                    if self.buffer.len() >= self.max_len {
+                        self.timer = None;
+                        return Poll::Ready(Ok(take(&mut self.buffer)));
+                    }
+                }
+                Poll::Ready(Err(error)) => self.error = Some(error),
+                Poll::Pending => break,
+            }
+        }
+
+        let has_expired = match self.timer.as_mut() {
+            Some(timer) => Pin::new(timer).poll(cx).is_ready(),
+            None => false,
+        };
+        if (has_expired || self.error.is_some()) && !self.buffer.is_empty() {
+            self.timer = None;
+            return Poll::Ready(Ok(take(&mut self.buffer)));
+        }
+        match self.error {
+            Some(error) => Poll::Ready(Err(error)),
+            None => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+/// A waker that unparks the thread which is blocked on a future.
+struct ThreadWaker {
+    thread: Thread,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.thread.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.thread.unpark();
+    }
+}
+
+thread_local! {
+    // Reusing the same waker lets futures recognize it with `will_wake`
+    // instead of registering a new waker on every call.
+    static THREAD_WAKER: Waker = Waker::from(Arc::new(ThreadWaker {
+        thread: thread::current(),
+    }));
+}
+
+/// Blocks the current thread until the future is complete.
+/// This should not be called from inside an async runtime,
+/// as it would stop other tasks on the same thread from making progress.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = THREAD_WAKER.with(Waker::clone);
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Blocks the current thread until the future is complete
+/// or the timeout has passed.
+/// Returns `None` if the future did not complete in time.
+pub fn block_on_timeout<F: Future>(
+    future: F,
+    timeout: Duration,
+) -> Option<F::Output> {
+    let deadline = Instant::now() + timeout;
+    let mut future = pin!(future);
+    let waker = THREAD_WAKER.with(Waker::clone);
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        thread::park_timeout(deadline - now);
+    }
+}
@@ -1,15 +1,75 @@
-use std::collections::VecDeque;
+use crate::adapters::SignalSource;
+#[cfg(not(target_family = "wasm"))]
+use crate::blocking::{block_on, block_on_timeout};
+use crate::error::RecvError;
+use crate::metrics::{
+    register_channel, ChannelCounters, ChannelStats, InspectChannel,
+};
+use crate::session::session_id;
+use crate::shutdown::{EventFuture, SHUTDOWN_EVENTS};
+use crate::sync::lock;
+use crate::time::now;
+use atomic_waker::AtomicWaker;
+use crossbeam_queue::SegQueue;
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+// Thread-blocking operations are possible
+// only on non-web platforms.
+#[cfg(not(target_family = "wasm"))]
+use std::sync::Condvar;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+/// The longest time that a sender is blocked by `ChannelPolicy::Block`
+/// before its message is discarded.
+#[cfg(not(target_family = "wasm"))]
+const MAX_BLOCK_DURATION: Duration = Duration::from_secs(1);
+
+/// How often a blocked sender checks whether Dart has stopped.
+#[cfg(not(target_family = "wasm"))]
+const BLOCK_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Decides how a signal channel treats messages
+/// that arrive faster than they are received.
+/// The capacity of a bounded policy is the maximum number
+/// of messages that can wait in the queue at once,
+/// and a capacity of zero is treated as one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChannelPolicy {
+    /// Keeps every message. This is the default.
+    #[default]
+    Unbounded,
+    /// Blocks the sender until the receiver makes room in the queue.
+    /// The sender waits for at most a second, and gives up
+    /// as soon as Dart stops, discarding the new message,
+    /// so that a stalled receiver cannot freeze Dart's UI thread.
+    /// On the web, where threads cannot be blocked,
+    /// the queue is allowed to grow instead.
+    Block(usize),
+    /// Discards the oldest queued message to make room for a new one.
+    DropOldest(usize),
+    /// Discards the new message when the queue is full.
+    DropNewest(usize),
+    /// Keeps only the most recent message.
+    KeepLatest,
+    /// Discards messages that were not received
+    /// within the given duration after being sent.
+    Expire(Duration),
+}
 
 /// The `SignalSender` is used to send messages into a shared message queue.
 /// It is clonable, and multiple senders can be created to send messages into
 /// the same queue. Each message is sent to a receiver, but only the currently
 /// active receiver can receive messages.
+/// The channel is closed when all senders are dropped.
 pub struct SignalSender<T> {
-    inner: Arc<Mutex<SignalChannel<T>>>,
+    inner: Arc<SharedChannel<T>>,
 }
 
 /// The `SignalReceiver` is used to asynchronously receive messages from the
@@ -17,34 +77,695 @@ pub struct SignalSender<T> {
 /// receivers are created by cloning the original. When a receiver is cloned,
 /// it becomes the active receiver, and the previous receiver will no longer
 /// receive messages.
+/// In a broadcast channel, every receiver stays active
+/// and receives its own copy of each message instead.
 pub struct SignalReceiver<T> {
-    inner: Arc<Mutex<SignalChannel<T>>>,
-    id: usize, // Each receiver has a unique ID
+    inner: Arc<SharedChannel<T>>,
+    id: usize,      // Each receiver has a unique ID
+    session: usize, // The Dart session that this receiver belongs to
+    // Kept between polls of `poll_recv` and `poll_next`
+    dart_stopped: Option<EventFuture>,
+}
+
+/// The channel state shared by senders and receivers.
+/// Receiver IDs, sender counts, and the closed flag are atomic
+/// so that they can be read without locking the message queue.
+struct SharedChannel<T> {
+    queue: SharedQueue<T>,
+    active_receiver_id: AtomicUsize, // Track the active receiver by ID
+    last_receiver_id: AtomicUsize,   // The most recently assigned ID
+    senders: AtomicUsize,            // Number of senders that are alive
+    closed: AtomicBool,
+    counters: ChannelCounters,
 }
 
-/// A channel holding a message queue and managing the current active receiver.
-/// Only the active receiver can receive messages.
+/// Storage of the messages waiting in a channel.
+enum SharedQueue<T> {
+    /// A lock-free queue used by unbounded channels,
+    /// so that senders never wait for each other or for the receiver.
+    /// Only the active receiver registers its waker.
+    LockFree {
+        queue: SegQueue<T>,
+        waker: AtomicWaker,
+        // Orders waker registration against receiver switching.
+        // Senders never take this lock.
+        register_lock: Mutex<()>,
+    },
+    /// A queue guarded by a mutex, used by channels
+    /// that have a policy or broadcast messages.
+    Locked {
+        state: Mutex<SignalChannel<T>>,
+        // Notifies senders blocked by the policy when there's room
+        #[cfg(not(target_family = "wasm"))]
+        condvar: Condvar,
+    },
+}
+
+/// A message queue that needs locking,
+/// along with the wakers of waiting receivers.
 struct SignalChannel<T> {
-    queue: VecDeque<T>,
-    waker: Option<Waker>,
-    active_receiver_id: usize, // Track the active receiver by ID
+    queue: VecDeque<QueuedMessage<T>>,
+    wakers: Vec<(usize, Waker)>, // Wakers of waiting receivers by ID
+    policy: ChannelPolicy,
+    dropped: usize, // Messages discarded by the policy
+    broadcast: Option<Broadcast<T>>,
+    sticky: Option<Sticky<T>>,
+}
+
+/// State of a channel that replays the most recent message
+/// to each new receiver.
+struct Sticky<T> {
+    clone_msg: fn(&T) -> T,
+    latest: Option<QueuedMessage<T>>,
+}
+
+/// State of a channel where every receiver gets a copy of each message.
+/// Messages stay in the queue until the capacity is exceeded,
+/// and each receiver keeps its own position in the queue.
+struct Broadcast<T> {
+    capacity: usize,
+    clone_msg: fn(&T) -> T,
+    head_seq: usize, // Sequence number of the front message
+    cursors: HashMap<usize, Cursor>, // Positions of receivers by ID
+}
+
+/// Position of a broadcast receiver in the message queue.
+struct Cursor {
+    next_seq: usize, // Sequence number of the next message to receive
+    lagged: usize,   // Messages that were skipped because of lagging
+}
+
+/// A message waiting in the queue, along with the time
+/// after which it should be discarded, if any.
+struct QueuedMessage<T> {
+    msg: T,
+    expires_at: Option<Duration>,
+}
+
+impl<T> SharedChannel<T> {
+    /// Checks if the receiver with the given ID has a message to receive.
+    /// If no message is available, the task will be put to sleep until
+    /// a message is sent or the Dart session ends.
+    /// If the receiver cannot receive messages anymore,
+    /// this will return the reason.
+    /// The future that waits for Dart to stop is created
+    /// on the first pending poll and should be kept by the caller.
+    fn poll_recv(
+        &self,
+        receiver_id: usize,
+        session: usize,
+        dart_stopped: &mut Option<EventFuture>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, RecvError>> {
+        match &self.queue {
+            SharedQueue::LockFree {
+                queue,
+                waker,
+                register_lock,
+            } => {
+                let poll = self.try_take(receiver_id, session);
+                if poll.is_ready() {
+                    // Wake the current task immediately
+                    // if more messages are in the queue
+                    if !queue.is_empty() {
+                        cx.waker().wake_by_ref();
+                    }
+                    return poll;
+                }
+                // Only the active receiver may occupy the waker slot
+                let register_guard = lock(register_lock);
+                if self.active_receiver_id.load(Ordering::SeqCst) != receiver_id
+                {
+                    return Poll::Ready(Err(RecvError::Superseded));
+                }
+                waker.register(cx.waker());
+                drop(register_guard);
+                // Check again in case a message was sent in the meantime
+                let poll = self.try_take(receiver_id, session);
+                if poll.is_ready() {
+                    return poll;
+                }
+            }
+            SharedQueue::Locked { state, .. } => {
+                let mut guard = lock(state);
+                let poll =
+                    self.try_take_locked(&mut guard, receiver_id, session);
+                if poll.is_ready() {
+                    // Check if more messages are in the queue
+                    if guard.has_next(receiver_id) {
+                        // If so, wake the current task immediately
+                        cx.waker().wake_by_ref();
+                    }
+                    return poll;
+                }
+                guard.register_waker(receiver_id, cx.waker());
+            }
+        }
+        // Wake up the task when Dart stops as well
+        let dart_stopped = dart_stopped
+            .get_or_insert_with(|| SHUTDOWN_EVENTS.dart_stopped.wait_async());
+        if Pin::new(dart_stopped).poll(cx).is_ready() {
+            // Messages sent in the meantime are still received first
+            let poll = self.try_take(receiver_id, session);
+            if poll.is_pending() {
+                return Poll::Ready(Err(RecvError::SessionEnded));
+            }
+            return poll;
+        }
+        Poll::Pending
+    }
+
+    /// Takes the next message for the receiver with the given ID
+    /// without waiting. Returns `Poll::Pending` if there's no message yet.
+    fn try_take(
+        &self,
+        receiver_id: usize,
+        session: usize,
+    ) -> Poll<Result<T, RecvError>> {
+        match &self.queue {
+            SharedQueue::LockFree { queue, .. } => {
+                // Receivers from a previous Dart session
+                // cannot receive messages
+                if session_id() != session {
+                    return Poll::Ready(Err(RecvError::SessionEnded));
+                }
+                // Only allow the current active receiver to receive messages
+                if self.active_receiver_id.load(Ordering::SeqCst) != receiver_id
+                {
+                    return Poll::Ready(Err(RecvError::Superseded));
+                }
+                // Read the flag before taking from the queue, so that
+                // messages sent before closing are not missed
+                let closed = self.closed.load(Ordering::SeqCst);
+                match queue.pop() {
+                    Some(msg) => {
+                        self.counters.record_received();
+                        Poll::Ready(Ok(msg))
+                    }
+                    None => self.poll_empty(closed),
+                }
+            }
+            SharedQueue::Locked { state, .. } => {
+                let mut guard = lock(state);
+                self.try_take_locked(&mut guard, receiver_id, session)
+            }
+        }
+    }
+
+    /// Takes the next message from the locked channel state
+    /// for the receiver with the given ID.
+    fn try_take_locked(
+        &self,
+        guard: &mut MutexGuard<'_, SignalChannel<T>>,
+        receiver_id: usize,
+        session: usize,
+    ) -> Poll<Result<T, RecvError>> {
+        // Receivers from a previous Dart session cannot receive messages
+        if session_id() != session {
+            return Poll::Ready(Err(RecvError::SessionEnded));
+        }
+        // Only allow the current active receiver to receive messages
+        let is_active = match guard.broadcast.as_ref() {
+            Some(broadcast) => broadcast.cursors.contains_key(&receiver_id),
+            None => {
+                self.active_receiver_id.load(Ordering::SeqCst) == receiver_id
+            }
+        };
+        if !is_active {
+            return Poll::Ready(Err(RecvError::Superseded));
+        }
+        if let Some(msg) = guard.take_next(receiver_id) {
+            // Let a blocked sender know that there's room now
+            #[cfg(not(target_family = "wasm"))]
+            if let SharedQueue::Locked { condvar, .. } = &self.queue {
+                condvar.notify_all();
+            }
+            self.counters.record_received();
+            Poll::Ready(Ok(msg))
+        } else {
+            self.poll_empty(self.closed.load(Ordering::SeqCst))
+        }
+    }
+
+    /// Decides what an empty queue means for the receiver.
+    /// Once Dart has stopped, the session ends
+    /// after every queued message has been received.
+    fn poll_empty(&self, closed: bool) -> Poll<Result<T, RecvError>> {
+        if closed {
+            Poll::Ready(Err(RecvError::Closed))
+        } else if SHUTDOWN_EVENTS.dart_stopped.is_set() {
+            Poll::Ready(Err(RecvError::SessionEnded))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Sends a message to the queue and wakes up the waiting receivers.
+    fn send(&self, msg: T) {
+        match &self.queue {
+            SharedQueue::LockFree { queue, waker, .. } => {
+                if self.closed.load(Ordering::SeqCst) {
+                    return;
+                }
+                queue.push(msg);
+                self.counters.record_sent(queue.len());
+                waker.wake();
+            }
+            SharedQueue::Locked {
+                state,
+                #[cfg(not(target_family = "wasm"))]
+                condvar,
+            } => {
+                let mut guard = lock(state);
+
+                // Wait until the receiver makes room,
+                // if the policy requires it
+                #[cfg(not(target_family = "wasm"))]
+                if guard.is_full() {
+                    let deadline = Instant::now() + MAX_BLOCK_DURATION;
+                    while guard.is_full() && !self.closed.load(Ordering::SeqCst)
+                    {
+                        // Give up instead of blocking the sender indefinitely
+                        let remaining =
+                            deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero()
+                            || SHUTDOWN_EVENTS.dart_stopped.is_set()
+                        {
+                            guard.dropped += 1;
+                            return;
+                        }
+                        let timeout = remaining.min(BLOCK_CHECK_INTERVAL);
+                        guard = match condvar.wait_timeout(guard, timeout) {
+                            Ok((inner, _)) => inner,
+                            Err(poisoned) => poisoned.into_inner().0,
+                        };
+                    }
+                }
+
+                // Enqueue the message
+                if self.closed.load(Ordering::SeqCst) || !guard.make_room() {
+                    return;
+                }
+                let expires_at = match guard.policy {
+                    ChannelPolicy::Expire(lifetime) => Some(now() + lifetime),
+                    _ => None,
+                };
+                if let Some(sticky) = guard.sticky.as_mut() {
+                    sticky.latest = Some(QueuedMessage {
+                        msg: (sticky.clone_msg)(&msg),
+                        expires_at,
+                    });
+                }
+                guard.queue.push_back(QueuedMessage { msg, expires_at });
+                self.counters.record_sent(guard.backlog());
+                // Wake up the waiting receivers, if any
+                guard.wake_all();
+            }
+        }
+    }
+
+    /// Assigns an ID to a new receiver and lets it receive messages.
+    /// In a broadcast channel, the new receiver starts
+    /// from the messages sent after this point.
+    /// In a sticky channel, the new receiver starts
+    /// from the most recent message instead.
+    fn add_receiver(&self) -> usize {
+        let receiver_id =
+            self.last_receiver_id.fetch_add(1, Ordering::SeqCst) + 1;
+        match &self.queue {
+            SharedQueue::LockFree {
+                waker,
+                register_lock,
+                ..
+            } => {
+                let register_guard = lock(register_lock);
+                let previous_id =
+                    self.active_receiver_id.swap(receiver_id, Ordering::SeqCst);
+                drop(register_guard);
+                self.record_handoff(previous_id);
+                // Let the previous receiver notice that it was superseded
+                waker.wake();
+            }
+            SharedQueue::Locked { state, .. } => {
+                let mut guard = lock(state);
+                let queue_len = guard.queue.len();
+                if let Some(broadcast) = guard.broadcast.as_mut() {
+                    let cursor = Cursor {
+                        next_seq: broadcast.head_seq + queue_len,
+                        lagged: 0,
+                    };
+                    broadcast.cursors.insert(receiver_id, cursor);
+                } else {
+                    let previous_id = self
+                        .active_receiver_id
+                        .swap(receiver_id, Ordering::SeqCst);
+                    self.record_handoff(previous_id);
+                    guard.replay_latest();
+                    guard.wake_all();
+                }
+            }
+        }
+        receiver_id
+    }
+
+    /// Counts the previous receiver as superseded.
+    /// Taking over from the original receiver is not counted,
+    /// as generated channels keep it in a static only to clone it.
+    fn record_handoff(&self, previous_id: usize) {
+        if previous_id != START_RECEIVER_ID {
+            self.counters.record_superseded();
+        }
+    }
+
+    /// Stops tracking the receiver with the given ID.
+    fn remove_receiver(&self, receiver_id: usize) {
+        if let SharedQueue::Locked { state, .. } = &self.queue {
+            let mut guard = lock(state);
+            guard.wakers.retain(|(id, _)| *id != receiver_id);
+            if let Some(broadcast) = guard.broadcast.as_mut() {
+                broadcast.cursors.remove(&receiver_id);
+            }
+        }
+    }
+
+    /// Discards every queued message, including the one
+    /// that a sticky channel would replay,
+    /// and wakes up everyone waiting on the channel.
+    fn reset(&self) {
+        match &self.queue {
+            SharedQueue::LockFree { queue, waker, .. } => {
+                while queue.pop().is_some() {}
+                waker.wake();
+            }
+            SharedQueue::Locked {
+                state,
+                #[cfg(not(target_family = "wasm"))]
+                condvar,
+            } => {
+                let mut guard = lock(state);
+                let queue_len = guard.queue.len();
+                guard.queue.clear();
+                if let Some(broadcast) = guard.broadcast.as_mut() {
+                    // Move every receiver past the discarded messages
+                    broadcast.head_seq += queue_len;
+                    for cursor in broadcast.cursors.values_mut() {
+                        cursor.next_seq = broadcast.head_seq;
+                    }
+                }
+                if let Some(sticky) = guard.sticky.as_mut() {
+                    sticky.latest = None;
+                }
+                guard.wake_all();
+                #[cfg(not(target_family = "wasm"))]
+                condvar.notify_all();
+            }
+        }
+    }
+
+    /// Marks the channel as closed and wakes up everyone waiting on it.
+    fn close(&self) {
+        match &self.queue {
+            SharedQueue::LockFree { waker, .. } => {
+                self.closed.store(true, Ordering::SeqCst);
+                waker.wake();
+            }
+            SharedQueue::Locked {
+                state,
+                #[cfg(not(target_family = "wasm"))]
+                condvar,
+            } => {
+                let mut guard = lock(state);
+                self.closed.store(true, Ordering::SeqCst);
+                guard.wake_all();
+                #[cfg(not(target_family = "wasm"))]
+                condvar.notify_all();
+            }
+        }
+    }
+
+    /// Returns the number of messages that were discarded by the policy.
+    fn dropped(&self) -> usize {
+        match &self.queue {
+            SharedQueue::LockFree { .. } => 0,
+            SharedQueue::Locked { state, .. } => lock(state).dropped,
+        }
+    }
+
+    /// Returns the number of messages waiting to be received.
+    fn depth(&self) -> usize {
+        match &self.queue {
+            SharedQueue::LockFree { queue, .. } => queue.len(),
+            SharedQueue::Locked { state, .. } => lock(state).backlog(),
+        }
+    }
+
+    /// Returns the number of messages that the given receiver
+    /// skipped because it fell behind in a broadcast channel.
+    fn lagged(&self, receiver_id: usize) -> usize {
+        match &self.queue {
+            SharedQueue::LockFree { .. } => 0,
+            SharedQueue::Locked { state, .. } => lock(state)
+                .broadcast
+                .as_ref()
+                .and_then(|broadcast| broadcast.cursors.get(&receiver_id))
+                .map_or(0, |cursor| cursor.lagged),
+        }
+    }
+}
+
+impl<T> SignalChannel<T> {
+    /// Registers the waker of a receiver,
+    /// replacing the one previously registered by the same receiver.
+    fn register_waker(&mut self, receiver_id: usize, waker: &Waker) {
+        match self.wakers.iter_mut().find(|(id, _)| *id == receiver_id) {
+            Some((_, existing)) => existing.clone_from(waker),
+            None => self.wakers.push((receiver_id, waker.clone())),
+        }
+    }
+
+    /// Wakes all receivers that are waiting for messages.
+    fn wake_all(&mut self) {
+        for (_, waker) in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Tries to take the next message for the given receiver.
+    /// Returns `None` if the receiver has nothing to receive yet.
+    fn take_next(&mut self, receiver_id: usize) -> Option<T> {
+        let Some(broadcast) = self.broadcast.as_mut() else {
+            self.discard_expired();
+            return self.queue.pop_front().map(|queued| queued.msg);
+        };
+        let cursor = broadcast.cursors.get_mut(&receiver_id)?;
+        // Skip messages that were pushed out while lagging behind
+        if cursor.next_seq < broadcast.head_seq {
+            cursor.lagged += broadcast.head_seq - cursor.next_seq;
+            cursor.next_seq = broadcast.head_seq;
+        }
+        let queued = self.queue.get(cursor.next_seq - broadcast.head_seq)?;
+        cursor.next_seq += 1;
+        Some((broadcast.clone_msg)(&queued.msg))
+    }
+
+    /// Returns the number of messages that are still to be received.
+    /// A broadcast queue keeps messages that everyone has received
+    /// as history, so its backlog is that of the slowest receiver.
+    /// The original receiver is not counted once others were cloned
+    /// from it, as generated channels keep it in a static.
+    fn backlog(&self) -> usize {
+        let Some(broadcast) = self.broadcast.as_ref() else {
+            return self.queue.len();
+        };
+        let tail_seq = broadcast.head_seq + self.queue.len();
+        let has_clones = broadcast.cursors.len() > 1;
+        broadcast
+            .cursors
+            .iter()
+            .filter(|(id, _)| !has_clones || **id != START_RECEIVER_ID)
+            .map(|(_, cursor)| {
+                tail_seq.saturating_sub(cursor.next_seq.max(broadcast.head_seq))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Replaces the backlog with the most recent message
+    /// so that a new receiver starts from it.
+    /// This does nothing if the channel is not sticky.
+    fn replay_latest(&mut self) {
+        let Some(sticky) = self.sticky.as_ref() else {
+            return;
+        };
+        let Some(latest) = sticky.latest.as_ref() else {
+            return;
+        };
+        let replayed = QueuedMessage {
+            msg: (sticky.clone_msg)(&latest.msg),
+            expires_at: latest.expires_at,
+        };
+        // Older messages are stale for the new receiver,
+        // while the last queued one is the message being replayed
+        self.dropped += self.queue.len().saturating_sub(1);
+        self.queue.clear();
+        self.queue.push_back(replayed);
+    }
+
+    /// Checks whether the given receiver has more messages to receive.
+    fn has_next(&self, receiver_id: usize) -> bool {
+        let Some(broadcast) = self.broadcast.as_ref() else {
+            return !self.queue.is_empty();
+        };
+        match broadcast.cursors.get(&receiver_id) {
+            Some(cursor) => {
+                cursor.next_seq < broadcast.head_seq + self.queue.len()
+            }
+            None => false,
+        }
+    }
+
+    /// Removes messages from the front of the queue
+    /// whose lifetime has passed.
+    fn discard_expired(&mut self) {
+        if !matches!(self.policy, ChannelPolicy::Expire(_)) {
+            return;
+        }
+        let current_time = now();
+        while let Some(queued) = self.queue.front() {
+            match queued.expires_at {
+                Some(expires_at) if expires_at <= current_time => {
+                    self.queue.pop_front();
+                    self.dropped += 1;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Makes room for a new message according to the policy.
+    /// Returns `false` if the new message should be discarded.
+    fn make_room(&mut self) -> bool {
+        if let Some(broadcast) = self.broadcast.as_mut() {
+            // Push the oldest message out, leaving it to lagging receivers
+            if self.queue.len() >= broadcast.capacity.max(1) {
+                self.queue.pop_front();
+                broadcast.head_seq += 1;
+            }
+            return true;
+        }
+        match self.policy {
+            ChannelPolicy::Unbounded | ChannelPolicy::Block(_) => true,
+            ChannelPolicy::Expire(_) => {
+                self.discard_expired();
+                true
+            }
+            ChannelPolicy::DropOldest(capacity) => {
+                while self.queue.len() >= capacity.max(1) {
+                    self.queue.pop_front();
+                    self.dropped += 1;
+                }
+                true
+            }
+            ChannelPolicy::DropNewest(capacity) => {
+                if self.queue.len() >= capacity.max(1) {
+                    self.dropped += 1;
+                    false
+                } else {
+                    true
+                }
+            }
+            ChannelPolicy::KeepLatest => {
+                self.dropped += self.queue.len();
+                self.queue.clear();
+                true
+            }
+        }
+    }
+
+    /// Checks whether the sender should wait before enqueuing.
+    #[cfg(not(target_family = "wasm"))]
+    fn is_full(&self) -> bool {
+        match self.policy {
+            ChannelPolicy::Block(capacity) => {
+                self.queue.len() >= capacity.max(1)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: Send> InspectChannel for SharedChannel<T> {
+    fn stats(&self) -> ChannelStats {
+        self.counters.stats(self.depth(), self.dropped())
+    }
+
+    fn reset(&self) {
+        SharedChannel::reset(self);
+    }
 }
 
 impl<T> SignalSender<T> {
     /// Sends a message to the shared queue. If a receiver is waiting for a
     /// message, it will be woken up. This method does not fail if the mutex
     /// is poisoned but simply ignores the failure.
+    /// Depending on the channel policy, the message might be discarded
+    /// or this method might block until the queue has room.
     pub fn send(&self, msg: T) {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        self.inner.send(msg);
+    }
 
-        // Enqueue the message
-        guard.queue.push_back(msg);
-        // Wake up the previous receiver making it receive `None`, if any
-        if let Some(waker) = guard.waker.take() {
-            waker.wake();
+    /// Returns the number of messages that were discarded
+    /// because of the channel policy.
+    pub fn dropped_count(&self) -> usize {
+        self.inner.dropped()
+    }
+
+    /// Closes the channel for all senders and receivers.
+    /// Messages sent after this are discarded, while receivers can still
+    /// receive the messages that are already in the queue.
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    /// Checks whether the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the counters describing the traffic of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.inner
+            .counters
+            .stats(self.inner.depth(), self.inner.dropped())
+    }
+}
+
+impl<T: Send + 'static> SignalSender<T> {
+    /// Adds the channel to the registry
+    /// so that it appears in channel snapshots.
+    #[doc(hidden)]
+    pub fn register(&self, message_id: i32, name: &'static str) {
+        let channel: Arc<dyn InspectChannel> = self.inner.clone();
+        register_channel(message_id, name, Arc::downgrade(&channel));
+    }
+}
+
+impl<T> Clone for SignalSender<T> {
+    /// Creates another sender for the same channel.
+    fn clone(&self) -> Self {
+        self.inner.senders.fetch_add(1, Ordering::SeqCst);
+        SignalSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for SignalSender<T> {
+    /// Closes the channel if this was the last sender.
+    fn drop(&mut self) {
+        let is_last = self.inner.senders.fetch_sub(1, Ordering::SeqCst) == 1;
+        if is_last {
+            self.inner.close();
         }
     }
 }
@@ -53,13 +774,94 @@ impl<T> SignalReceiver<T> {
     /// Asynchronously receives the next message from the queue. Only the active
     /// receiver is allowed to receive messages. If there are no messages in the
     /// queue, the receiver will wait until a new message is sent.
-    /// If this receiver is not active, the future will return `None`.
-    pub fn recv(&self) -> impl Future<Output = Option<T>> {
+    /// If this receiver cannot receive messages anymore,
+    /// the future will return the reason as an error.
+    pub fn recv(&self) -> impl Future<Output = Result<T, RecvError>> {
         RecvFuture {
             inner: self.inner.clone(),
             receiver_id: self.id, // Pass the receiver's ID to the future
+            session: self.session,
+            dart_stopped: None,
         }
     }
+
+    /// Receives the next message from the queue without waiting.
+    /// Returns `None` if the queue is empty.
+    pub fn try_recv(&self) -> Result<Option<T>, RecvError> {
+        match self.inner.try_take(self.id, self.session) {
+            Poll::Ready(result) => result.map(Some),
+            Poll::Pending => Ok(None),
+        }
+    }
+
+    /// Receives all messages currently in the queue without waiting.
+    /// The returned list is empty if this receiver
+    /// cannot receive messages anymore.
+    pub fn drain(&self) -> Vec<T> {
+        let mut msgs = Vec::new();
+        while let Poll::Ready(Ok(msg)) =
+            self.inner.try_take(self.id, self.session)
+        {
+            msgs.push(msg);
+        }
+        msgs
+    }
+
+    /// Blocks the current thread until the next message is received.
+    /// This is useful on dedicated threads without an async context,
+    /// but should not be called from inside an async runtime.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn recv_blocking(&self) -> Result<T, RecvError> {
+        block_on(self.recv())
+    }
+
+    /// Blocks the current thread until the next message is received
+    /// or the timeout has passed.
+    /// Returns `None` if the timeout has passed.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn recv_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<T>, RecvError> {
+        block_on_timeout(self.recv(), timeout).transpose()
+    }
+
+    /// Receives the next message from the queue.
+    /// On the web, where threads cannot be blocked,
+    /// this behaves like `try_recv` regardless of the timeout.
+    #[cfg(target_family = "wasm")]
+    pub fn recv_timeout(
+        &self,
+        _timeout: Duration,
+    ) -> Result<Option<T>, RecvError> {
+        self.try_recv()
+    }
+
+    /// Checks whether the channel is closed.
+    /// Messages that are already in the queue can still be received.
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of messages that were discarded
+    /// because of the channel policy.
+    pub fn dropped_count(&self) -> usize {
+        self.inner.dropped()
+    }
+
+    /// Returns the number of messages that this receiver skipped
+    /// because it fell behind in a broadcast channel.
+    /// This is always zero for other channels.
+    pub fn lagged_count(&self) -> usize {
+        self.inner.lagged(self.id)
+    }
+
+    /// Returns the counters describing the traffic of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.inner
+            .counters
+            .stats(self.inner.depth(), self.inner.dropped())
+    }
 }
 
 // Automatically make the cloned receiver the active one
@@ -67,20 +869,23 @@ impl<T> Clone for SignalReceiver<T> {
     /// Clones the receiver and makes the new receiver the active one. The
     /// original receiver will no longer receive messages after this clone.
     /// This ensures only the most recent receiver can access the message queue.
+    /// In a broadcast channel, the new receiver subscribes to
+    /// messages sent from now on, and the original receiver stays active.
     fn clone(&self) -> Self {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        let new_receiver = SignalReceiver {
+        SignalReceiver {
             inner: self.inner.clone(),
-            id: guard.active_receiver_id + 1, // Increment ID for new receiver
-        };
-        guard.active_receiver_id = new_receiver.id;
-        if let Some(waker) = guard.waker.take() {
-            waker.wake();
+            id: self.inner.add_receiver(), // Increment ID for new receiver
+            session: session_id(),
+            dart_stopped: None,
         }
-        new_receiver
+    }
+}
+
+impl<T> Drop for SignalReceiver<T> {
+    /// Stops tracking the position of this receiver
+    /// so that it doesn't occupy memory in the channel.
+    fn drop(&mut self) {
+        self.inner.remove_receiver(self.id);
     }
 }
 
@@ -88,40 +893,57 @@ impl<T> Clone for SignalReceiver<T> {
 /// message. This future is only completed when the active receiver receives
 /// a message from the queue.
 struct RecvFuture<T> {
-    inner: Arc<Mutex<SignalChannel<T>>>,
+    inner: Arc<SharedChannel<T>>,
     receiver_id: usize, // Track which receiver is polling
+    session: usize,
+    dart_stopped: Option<EventFuture>,
 }
 
 impl<T> Future for RecvFuture<T> {
-    type Output = Option<T>;
+    type Output = Result<T, RecvError>;
 
     /// Polls the future to check if the active receiver has a message in the
     /// queue. If no message is available, the task will be put to sleep until
-    /// a message is sent. If this receiver is not the active receiver, it will
-    /// return `None`.
+    /// a message is sent. If this receiver cannot receive messages anymore,
+    /// it will return the reason as an error.
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        let this = self.get_mut();
+        this.inner.poll_recv(
+            this.receiver_id,
+            this.session,
+            &mut this.dart_stopped,
+            cx,
+        )
+    }
+}
 
-        // Only allow the current active receiver to receive messages
-        if guard.active_receiver_id == self.receiver_id {
-            if let Some(msg) = guard.queue.pop_front() {
-                // Check if more messages are in the queue
-                if !guard.queue.is_empty() {
-                    // If so, wake the current task immediately
-                    cx.waker().wake_by_ref();
-                }
-                Poll::Ready(Some(msg))
-            } else {
-                guard.waker = Some(cx.waker().clone());
-                Poll::Pending
-            }
-        } else {
-            // Return None if this receiver is not the current active one
-            Poll::Ready(None)
-        }
+impl<T> SignalSource for SignalReceiver<T> {
+    type Item = T;
+
+    /// Polls for the next message in the same way as `recv`.
+    fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Item, RecvError>> {
+        self.inner
+            .poll_recv(self.id, self.session, &mut self.dart_stopped, cx)
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> futures_core::Stream for SignalReceiver<T> {
+    type Item = T;
+
+    /// Polls for the next message in the same way as `recv`.
+    /// The stream ends when this receiver cannot receive messages anymore.
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.inner
+            .poll_recv(this.id, this.session, &mut this.dart_stopped, cx)
+            .map(|result| result.ok())
     }
 }
 
@@ -131,20 +953,123 @@ impl<T> Future for RecvFuture<T> {
 /// are created by cloning the original receiver.
 #[doc(hidden)]
 pub fn signal_channel<T>() -> (SignalSender<T>, SignalReceiver<T>) {
-    let start_receiver_id = 0;
+    signal_channel_with(ChannelPolicy::Unbounded)
+}
+
+/// Creates a message channel that handles
+/// excessive messages according to the given policy.
+#[doc(hidden)]
+pub fn signal_channel_with<T>(
+    policy: ChannelPolicy,
+) -> (SignalSender<T>, SignalReceiver<T>) {
+    // Channels without a policy don't need locking
+    if policy == ChannelPolicy::Unbounded {
+        return create_channel(SharedQueue::LockFree {
+            queue: SegQueue::new(),
+            waker: AtomicWaker::new(),
+            register_lock: Mutex::new(()),
+        });
+    }
+    let channel = SignalChannel {
+        queue: VecDeque::new(),
+        wakers: Vec::new(),
+        policy,
+        dropped: 0,
+        broadcast: None,
+        sticky: None,
+    };
+    create_channel(SharedQueue::Locked {
+        state: Mutex::new(channel),
+        #[cfg(not(target_family = "wasm"))]
+        condvar: Condvar::new(),
+    })
+}
+
+/// Creates a message channel where every receiver
+/// gets its own copy of each message.
+/// Cloning a receiver subscribes a new receiver
+/// instead of replacing the original one.
+/// Up to `capacity` recent messages are kept, and receivers that
+/// fall further behind skip the messages they missed.
+#[doc(hidden)]
+pub fn broadcast_signal_channel<T: Clone>(
+    capacity: usize,
+) -> (SignalSender<T>, SignalReceiver<T>) {
+    let start_cursor = Cursor {
+        next_seq: 0,
+        lagged: 0,
+    };
+    let channel = SignalChannel {
+        queue: VecDeque::new(),
+        wakers: Vec::new(),
+        policy: ChannelPolicy::Unbounded,
+        dropped: 0,
+        broadcast: Some(Broadcast {
+            capacity,
+            clone_msg: T::clone,
+            head_seq: 0,
+            cursors: HashMap::from([(START_RECEIVER_ID, start_cursor)]),
+        }),
+        sticky: None,
+    };
+    create_channel(SharedQueue::Locked {
+        state: Mutex::new(channel),
+        #[cfg(not(target_family = "wasm"))]
+        condvar: Condvar::new(),
+    })
+}
 
-    let channel = Arc::new(Mutex::new(SignalChannel {
+/// Creates a message channel where each new receiver
+/// first receives the most recent message, even if a previous receiver
+/// has already received it, and then receives messages sent after it.
+/// Older messages that were not received yet are discarded
+/// when a new receiver is created.
+#[doc(hidden)]
+pub fn sticky_signal_channel<T: Clone>(
+    policy: ChannelPolicy,
+) -> (SignalSender<T>, SignalReceiver<T>) {
+    let channel = SignalChannel {
         queue: VecDeque::new(),
-        waker: None,
-        active_receiver_id: start_receiver_id,
-    }));
+        wakers: Vec::new(),
+        policy,
+        dropped: 0,
+        broadcast: None,
+        sticky: Some(Sticky {
+            clone_msg: T::clone,
+            latest: None,
+        }),
+    };
+    create_channel(SharedQueue::Locked {
+        state: Mutex::new(channel),
+        #[cfg(not(target_family = "wasm"))]
+        condvar: Condvar::new(),
+    })
+}
+
+/// The ID of the receiver that is created along with the channel.
+const START_RECEIVER_ID: usize = 0;
+
+/// Wraps the message queue with a sender and a receiver.
+fn create_channel<T>(
+    queue: SharedQueue<T>,
+) -> (SignalSender<T>, SignalReceiver<T>) {
+    let channel = Arc::new(SharedChannel {
+        queue,
+        active_receiver_id: AtomicUsize::new(START_RECEIVER_ID),
+        last_receiver_id: AtomicUsize::new(START_RECEIVER_ID),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        counters: ChannelCounters::default(),
+    });
 
     let sender = SignalSender {
         inner: channel.clone(),
     };
     let receiver = SignalReceiver {
         inner: channel,
-        id: start_receiver_id,
+        id: START_RECEIVER_ID,
+        session: session_id(),
+        dart_stopped: None,
     };
     (sender, receiver)
 }
@@ -7,6 +7,7 @@ pub enum RinfError {
     CannotDecodeMessage,
     NoSignalHandler,
     NoBindings,
+    DeliveryFailed,
 }
 
 impl fmt::Display for RinfError {
@@ -24,8 +25,42 @@ impl fmt::Display for RinfError {
             Self::NoBindings => {
                 write!(f, "Rinf bindings are not ready")
             }
+            Self::DeliveryFailed => {
+                write!(f, "Dart did not accept the Rust signal")
+            }
         }
     }
 }
 
 impl Error for RinfError {}
+
+/// The reason why a signal receiver cannot receive messages anymore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvError {
+    /// Another receiver was cloned from this one and took its place.
+    Superseded,
+    /// The channel was closed, or all of its senders were dropped,
+    /// and no messages are left in the queue.
+    Closed,
+    /// The Dart session that this receiver belongs to has ended,
+    /// and no messages are left in the queue.
+    SessionEnded,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Superseded => {
+                write!(f, "The receiver was superseded by a newer one")
+            }
+            Self::Closed => {
+                write!(f, "The channel is closed")
+            }
+            Self::SessionEnded => {
+                write!(f, "The Dart session of the receiver has ended")
+            }
+        }
+    }
+}
+
+impl Error for RecvError {}
@@ -9,6 +9,7 @@ use super::interface_web::*;
 /// Optionally, a custom binary called `binary` can also be included.
 /// This type is generic, and the message
 /// can be of any type declared in Protobuf.
+#[derive(Clone)]
 #[cfg_attr(feature = "bevy", derive(bevy_ecs::event::Event))]
 pub struct DartSignal<T> {
     /// The message instance of a struct generated by Protobuf.
@@ -1,15 +1,176 @@
 use crate::error::RinfError;
-use crate::shutdown::SHUTDOWN_EVENTS;
+use crate::metrics::reset_channels;
+use crate::panic_report::install_panic_hook;
+use crate::session::begin_session;
+use crate::shutdown::{shutdown_grace_period, SHUTDOWN_EVENTS};
+use crate::supervisor::run_supervised;
+use crate::tasks::alive_tasks;
 use allo_isolate::ffi::DartPostCObjectFnType;
 use allo_isolate::{
     store_dart_post_cobject, IntoDart, Isolate, ZeroCopyBuffer,
 };
 use os_thread_local::ThreadLocal;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::sync::OnceLock;
 use std::thread;
 
-static DART_ISOLATE: Mutex<Option<Isolate>> = Mutex::new(None);
+/// The number of Rust signals kept by default
+/// while the Dart isolate is not bound.
+const DEFAULT_BUFFER_CAPACITY: usize = 256;
+
+/// The number of consecutive delivery failures
+/// after which the Dart isolate is considered dead.
+const DEAD_ISOLATE_THRESHOLD: usize = 3;
+
+/// The number of Rust signals that Dart did not accept.
+static DELIVERY_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of Rust signals discarded because the buffer was full.
+static DROPPED_SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+/// Decides which signal is discarded
+/// when the buffer for the Dart isolate is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BufferOverflow {
+    /// Discards the oldest buffered signal to make room for a new one.
+    #[default]
+    DropOldest,
+    /// Discards the new signal, returning `RinfError::NoDartIsolate`.
+    DropNewest,
+}
+
+/// A Rust signal waiting to be posted to Dart.
+struct RawSignal {
+    message_id: i32,
+    message_bytes: Vec<u8>,
+    binary: Vec<u8>,
+}
+
+/// The Dart isolate that receives Rust signals,
+/// along with the signals sent while it was not bound.
+struct DartConnection {
+    isolate: Option<Isolate>,
+    expected: bool, // Whether Dart has ever started Rust or bound an isolate
+    dead: bool,     // Whether the isolate was unbound after failures
+    pending: VecDeque<RawSignal>,
+    capacity: usize,
+    overflow: BufferOverflow,
+    consecutive_failures: usize,
+}
+
+impl DartConnection {
+    /// Posts the signal to the isolate, keeping track of failures.
+    /// After repeated failures, the port of the isolate is assumed
+    /// to be closed, and the isolate is unbound
+    /// so that new signals fail until Dart binds it again.
+    fn post(&mut self, signal: RawSignal) -> Result<(), RinfError> {
+        let dart_isolate =
+            self.isolate.as_ref().ok_or(RinfError::NoDartIsolate)?;
+        if post_signal(dart_isolate, signal) {
+            self.consecutive_failures = 0;
+            return Ok(());
+        }
+        DELIVERY_FAILURES.fetch_add(1, Ordering::SeqCst);
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= DEAD_ISOLATE_THRESHOLD {
+            self.isolate = None;
+            self.dead = true;
+            self.consecutive_failures = 0;
+        }
+        Err(RinfError::DeliveryFailed)
+    }
+
+    /// Keeps the signal until the isolate is bound,
+    /// counting the signals that are discarded when the buffer is full.
+    fn buffer(&mut self, signal: RawSignal) -> Result<(), RinfError> {
+        if self.pending.len() >= self.capacity {
+            DROPPED_SIGNALS.fetch_add(1, Ordering::SeqCst);
+            match self.overflow {
+                BufferOverflow::DropNewest => {
+                    return Err(RinfError::NoDartIsolate);
+                }
+                BufferOverflow::DropOldest => {
+                    if self.pending.pop_front().is_none() {
+                        // Buffering is disabled.
+                        return Err(RinfError::NoDartIsolate);
+                    }
+                }
+            }
+        }
+        self.pending.push_back(signal);
+        Ok(())
+    }
+
+    fn trim(&mut self) {
+        while self.pending.len() > self.capacity {
+            DROPPED_SIGNALS.fetch_add(1, Ordering::SeqCst);
+            match self.overflow {
+                BufferOverflow::DropOldest => self.pending.pop_front(),
+                BufferOverflow::DropNewest => self.pending.pop_back(),
+            };
+        }
+    }
+}
+
+static DART_CONNECTION: Mutex<DartConnection> = Mutex::new(DartConnection {
+    isolate: None,
+    expected: false,
+    dead: false,
+    pending: VecDeque::new(),
+    capacity: DEFAULT_BUFFER_CAPACITY,
+    overflow: BufferOverflow::DropOldest,
+    consecutive_failures: 0,
+});
+
+/// Sets how many Rust signals are kept while the Dart isolate
+/// is not bound, such as during early startup
+/// or after the Flutter engine was detached on Android.
+/// Buffered signals are delivered in order
+/// once Dart binds its isolate again.
+/// The default capacity is 256, with the oldest signal discarded
+/// when the buffer is full.
+/// A capacity of 0 disables buffering, so that sending fails
+/// with `RinfError::NoDartIsolate`.
+/// Signals are never buffered before Dart starts the Rust logic
+/// or binds its isolate, such as when running test code in Rust.
+pub fn set_signal_buffer(capacity: usize, overflow: BufferOverflow) {
+    let mut guard = match DART_CONNECTION.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.capacity = capacity;
+    guard.overflow = overflow;
+    guard.trim();
+}
+
+/// Returns whether a Dart isolate is bound to receive Rust signals.
+/// This becomes `false` after Dart has repeatedly failed
+/// to accept signals, which usually means that its port is closed,
+/// until Dart binds a new isolate.
+/// Senders can use this to back off from producing signals.
+pub fn dart_connected() -> bool {
+    let guard = match DART_CONNECTION.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.isolate.is_some()
+}
+
+/// Returns the number of Rust signals that Dart did not accept
+/// since the app started, including those sent
+/// while the Dart isolate was considered dead.
+pub fn delivery_failures() -> usize {
+    DELIVERY_FAILURES.load(Ordering::SeqCst)
+}
+
+/// Returns the number of Rust signals discarded
+/// because the buffer was full while the Dart isolate was not bound,
+/// since the app started.
+pub fn dropped_signals() -> usize {
+    DROPPED_SIGNALS.load(Ordering::SeqCst)
+}
 
 #[no_mangle]
 pub unsafe extern "C" fn prepare_isolate_extern(
@@ -18,11 +179,24 @@ pub unsafe extern "C" fn prepare_isolate_extern(
 ) {
     store_dart_post_cobject(store_post_object);
     let dart_isolate = Isolate::new(port);
-    let mut guard = match DART_ISOLATE.lock() {
+    let mut guard = match DART_CONNECTION.lock() {
         Ok(inner) => inner,
         Err(poisoned) => poisoned.into_inner(),
     };
-    guard.replace(dart_isolate);
+
+    // Deliver the signals sent while the isolate was not bound.
+    // The lock is held so that newer signals are posted after them.
+    guard.isolate = Some(dart_isolate);
+    guard.expected = true;
+    guard.dead = false;
+    guard.consecutive_failures = 0;
+    for signal in std::mem::take(&mut guard.pending) {
+        let _ = if guard.isolate.is_some() {
+            guard.post(signal)
+        } else {
+            guard.buffer(signal)
+        };
+    }
 }
 
 // We use `os_thread_local` so that when the program fails
@@ -39,8 +213,30 @@ pub struct ShutdownDropper;
 
 impl Drop for ShutdownDropper {
     fn drop(&mut self) {
+        // The grace period is measured before the hooks are taken,
+        // as it includes their timeouts.
+        let grace_period = shutdown_grace_period();
         SHUTDOWN_EVENTS.dart_stopped.set();
-        SHUTDOWN_EVENTS.rust_stopped.wait();
+        let Some(grace_period) = grace_period else {
+            SHUTDOWN_EVENTS.rust_stopped.wait();
+            return;
+        };
+        if SHUTDOWN_EVENTS.rust_stopped.wait_timeout(grace_period) {
+            return;
+        }
+        // Stop blocking so that the app can close anyway.
+        // Remaining Rust threads are terminated along with the process.
+        // Dart cannot print anymore, so the report goes to the console.
+        let task_names = alive_tasks();
+        let task_list = if task_names.is_empty() {
+            String::from("No tracked tasks are alive.")
+        } else {
+            format!("Tasks still alive: {}", task_names.join(", "))
+        };
+        eprintln!(
+            "Rust logic did not finish within {grace_period:?} \
+            after Dart stopped.\n{task_list}"
+        );
     }
 }
 
@@ -48,25 +244,9 @@ pub fn start_rust_logic_real<F, T>(main_fn: F) -> Result<(), RinfError>
 where
     F: Fn() -> T + Send + 'static,
 {
-    // Enable console output for panics.
-    #[cfg(debug_assertions)]
-    {
-        #[cfg(not(feature = "backtrace"))]
-        {
-            std::panic::set_hook(Box::new(|panic_info| {
-                crate::debug_print!("A panic occurred in Rust.\n{panic_info}");
-            }));
-        }
-        #[cfg(feature = "backtrace")]
-        {
-            std::panic::set_hook(Box::new(|panic_info| {
-                let backtrace = backtrace::Backtrace::new();
-                crate::debug_print!(
-                    "A panic occurred in Rust.\n{panic_info}\n{backtrace:?}"
-                );
-            }));
-        }
-    }
+    // Enable console output and reports for panics.
+    // This is done only once, keeping hooks installed before.
+    install_panic_hook();
 
     // Prepare the shutdown dropper that will notify the Rust async runtime
     // after Dart thread has exited.
@@ -74,24 +254,37 @@ where
     let thread_local = ThreadLocal::new(|| ShutdownDropper);
     let _ = SHUTDOWN_DROPPER.set(thread_local);
 
-    // Spawn a new thread to run the async runtime.
-    thread::spawn(move || {
-        // Notify that Dart has stopped
-        // to terminate the previous Rust async runtime threads.
-        // After Dart's hot restart or reopening the app,
-        // Previous Rust async runtime can be still running.
-        SHUTDOWN_EVENTS.dart_stopped.set();
+    // Keep Rust signals from now on until Dart binds its isolate.
+    expect_dart();
 
-        // Clear shutdown events to prepare for a fresh start.
-        SHUTDOWN_EVENTS.dart_stopped.clear();
-        SHUTDOWN_EVENTS.rust_stopped.clear();
+    // Notify that Dart has stopped
+    // to terminate the previous Rust async runtime threads.
+    // After Dart's hot restart or reopening the app,
+    // Previous Rust async runtime can be still running.
+    // This is done before returning to Dart,
+    // so that signals from the new Dart session are not discarded.
+    SHUTDOWN_EVENTS.dart_stopped.set();
 
+    // Clear shutdown events to prepare for a fresh start.
+    SHUTDOWN_EVENTS.dart_stopped.clear();
+    SHUTDOWN_EVENTS.rust_stopped.clear();
+
+    // Discard signals that the previous Dart session has sent.
+    reset_channels();
+
+    // Count the new session so that user code can tell
+    // a cold start from a restart.
+    begin_session();
+
+    // Spawn a new thread to run the async runtime.
+    thread::spawn(move || {
         // Execute the long-running function that will block the thread
         // for the entire lifecycle of the app.
         // This function runs the async Rust runtime.
-        main_fn();
+        // Panics are caught so that Dart is notified of the crash.
+        run_supervised(&main_fn);
 
-        // After the Rust async runtime is closed,
+        // After the Rust async runtime is closed or has crashed,
         // notify the main Dart thread to stop blocking
         // and allow the application to exit.
         SHUTDOWN_EVENTS.rust_stopped.set();
@@ -110,36 +303,69 @@ pub fn send_rust_signal_real(
     message_bytes: Vec<u8>,
     binary: Vec<u8>,
 ) -> Result<(), RinfError> {
-    // When `DART_ISOLATE` is not initialized, just return the error.
-    // This can happen when running test code in Rust.
-    let guard = match DART_ISOLATE.lock() {
+    let mut guard = match DART_CONNECTION.lock() {
         Ok(inner) => inner,
         Err(poisoned) => poisoned.into_inner(),
     };
-    let dart_isolate = guard.as_ref().ok_or(RinfError::NoDartIsolate)?;
+    let signal = RawSignal {
+        message_id,
+        message_bytes,
+        binary,
+    };
+    if guard.isolate.is_some() {
+        guard.post(signal)
+    } else if guard.dead {
+        // Dart is not expected to accept signals
+        // until it binds a new isolate.
+        DELIVERY_FAILURES.fetch_add(1, Ordering::SeqCst);
+        Err(RinfError::DeliveryFailed)
+    } else if guard.expected {
+        // When the Dart isolate is not bound yet, keep the signal
+        // so that it can be delivered later.
+        guard.buffer(signal)
+    } else {
+        // Without Dart, such as when running test code in Rust,
+        // the signal is rejected so that reports fall back to the console.
+        Err(RinfError::NoDartIsolate)
+    }
+}
 
+/// Marks that Dart is present, so that Rust signals
+/// are buffered while its isolate is not bound.
+/// A new Dart session is about to bind a new isolate,
+/// so a dead one no longer makes signals fail.
+fn expect_dart() {
+    let mut guard = match DART_CONNECTION.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.expected = true;
+    guard.dead = false;
+}
+
+/// Posts the signal to the isolate,
+/// returning whether Dart accepted it.
+fn post_signal(dart_isolate: &Isolate, signal: RawSignal) -> bool {
     // If a `Vec<u8>` is empty, we can't just simply send it to Dart
     // because panic can occur from null pointers.
     // Instead, we will reconstruct the empty vector from the Dart side.
-    let message_filled = !message_bytes.is_empty();
-    let binary_filled = !binary.is_empty();
+    let message_filled = !signal.message_bytes.is_empty();
+    let binary_filled = !signal.binary.is_empty();
 
     dart_isolate.post(
         vec![
-            message_id.into_dart(),
+            signal.message_id.into_dart(),
             if message_filled {
-                ZeroCopyBuffer(message_bytes).into_dart()
+                ZeroCopyBuffer(signal.message_bytes).into_dart()
             } else {
                 ().into_dart()
             },
             if binary_filled {
-                ZeroCopyBuffer(binary).into_dart()
+                ZeroCopyBuffer(signal.binary).into_dart()
             } else {
                 ().into_dart()
             },
         ]
         .into_dart(),
-    );
-
-    Ok(())
+    )
 }
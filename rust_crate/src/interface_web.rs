@@ -1,18 +1,34 @@
 use crate::error::RinfError;
-use js_sys::Uint8Array;
+use crate::metrics::reset_channels;
+use crate::panic_report::install_panic_hook;
+use crate::session::begin_session;
+use crate::shutdown::SHUTDOWN_EVENTS;
+use js_sys::{Reflect, Uint8Array};
+use std::sync::Once;
 use wasm_bindgen::prelude::*;
 
 pub fn start_rust_logic_real<F, T>(main_fn: F) -> Result<(), RinfError>
 where
     F: Fn() -> T + 'static,
 {
-    // Add kind description for panics.
-    #[cfg(debug_assertions)]
-    {
-        std::panic::set_hook(Box::new(|panic_info| {
-            crate::debug_print!("A panic occurred in Rust.\n{panic_info}");
-        }));
-    }
+    // Add kind description and reports for panics.
+    // This is done only once, keeping hooks installed before.
+    install_panic_hook();
+
+    // Notify Rust when the page is going away,
+    // so that shutdown work gets a chance to run.
+    listen_to_page_exit();
+
+    // End the previous session, if any, so that its receivers
+    // and shutdown tokens are released as on native platforms.
+    // The flag is cleared right away to prepare for a fresh start.
+    SHUTDOWN_EVENTS.dart_stopped.set();
+    SHUTDOWN_EVENTS.dart_stopped.clear();
+
+    // Discard signals that were sent before this session
+    // and count the new session before running the main function.
+    reset_channels();
+    begin_session();
 
     // Run the main function.
     main_fn();
@@ -20,6 +36,45 @@ where
     Ok(())
 }
 
+#[wasm_bindgen]
+pub fn stop_rust_logic_extern() {
+    SHUTDOWN_EVENTS.dart_stopped.set();
+}
+
+#[wasm_bindgen]
+extern "C" {
+    // Available in both the main JavaScript thread and web workers,
+    // though page events are only fired in the main JavaScript thread.
+    #[wasm_bindgen(js_name = addEventListener, catch)]
+    fn add_event_listener(
+        event_type: &str,
+        listener: &JsValue,
+    ) -> Result<(), JsValue>;
+}
+
+/// Sets `dart_stopped` when the page is unloaded.
+/// Futures waiting for it are resolved in the microtasks
+/// that run right after the event handler,
+/// so async shutdown work can run on a best-effort basis.
+fn listen_to_page_exit() {
+    static LISTENING: Once = Once::new();
+    LISTENING.call_once(|| {
+        let listener = Closure::<dyn FnMut(JsValue)>::new(|event: JsValue| {
+            // A page kept in the back-forward cache might be shown again
+            let persisted = Reflect::get(&event, &"persisted".into())
+                .map(|value| value.is_truthy())
+                .unwrap_or(false);
+            if !persisted {
+                SHUTDOWN_EVENTS.dart_stopped.set();
+            }
+        })
+        .into_js_value();
+        for event_type in ["pagehide", "unload"] {
+            let _ = add_event_listener(event_type, &listener);
+        }
+    });
+}
+
 #[wasm_bindgen]
 extern "C" {
     // The reason this extern function is marked `catch`
@@ -1,7 +1,20 @@
+mod adapters;
+#[cfg(not(target_family = "wasm"))]
+mod blocking;
 mod channel;
 mod error;
 mod macros;
+mod metrics;
+mod panic_report;
+mod session;
 mod shutdown;
+#[cfg(not(target_family = "wasm"))]
+mod supervisor;
+mod sync;
+mod system;
+mod tasks;
+mod time;
+mod watch;
 
 mod interface;
 #[cfg(not(target_family = "wasm"))]
@@ -9,7 +22,35 @@ mod interface_os;
 #[cfg(target_family = "wasm")]
 mod interface_web;
 
-pub use channel::{signal_channel, SignalReceiver, SignalSender};
-pub use error::RinfError;
+pub use adapters::{
+    ChunksTimeout, Debounce, Filter, Map, SignalSource, Throttle,
+};
+pub use channel::{
+    broadcast_signal_channel, signal_channel, signal_channel_with,
+    sticky_signal_channel, ChannelPolicy, SignalReceiver, SignalSender,
+};
+pub use error::{RecvError, RinfError};
 pub use interface::{send_rust_signal, start_rust_logic, DartSignal};
-pub use shutdown::dart_shutdown;
+#[cfg(not(target_family = "wasm"))]
+pub use interface_os::{
+    dart_connected, delivery_failures, dropped_signals, set_signal_buffer,
+    BufferOverflow,
+};
+pub use metrics::{
+    channel_snapshot, register_reset, ChannelSnapshot, ChannelStats,
+    MERGED_SIGNAL_ID,
+};
+#[cfg(not(target_family = "wasm"))]
+pub use panic_report::set_crash_directory;
+pub use panic_report::{set_panic_policy, set_panic_reports, PanicPolicy};
+pub use session::{session_id, session_info, SessionInfo, StartKind};
+pub use shutdown::{
+    dart_shutdown, on_shutdown, on_shutdown_with_timeout,
+    set_shutdown_grace_period, shutdown_token, CancellationToken, Event,
+    EventFuture,
+};
+#[cfg(not(target_family = "wasm"))]
+pub use supervisor::{set_restart_policy, RestartPolicy};
+pub use system::{assign_system_signal, system_event_receiver, SystemEvent};
+pub use tasks::{alive_tasks, track_task, TaskGuard};
+pub use watch::{watch_channel, WatchReceiver, WatchRef, WatchSender};
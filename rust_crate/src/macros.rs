@@ -35,8 +35,12 @@ macro_rules! write_interface {
             use std::slice::from_raw_parts;
             let message_bytes = from_raw_parts(message_pointer, message_size);
             let binary = from_raw_parts(binary_pointer, binary_size);
-            let result =
-                messages::assign_dart_signal(message_id, message_bytes, binary);
+            // Negative message IDs are reserved for system signals
+            let result = if message_id < 0 {
+                $crate::assign_system_signal(message_id, message_bytes, binary)
+            } else {
+                messages::assign_dart_signal(message_id, message_bytes, binary)
+            };
             if let Err(error) = result {
                 rinf::debug_print!("{error}");
             }
@@ -51,8 +55,12 @@ macro_rules! write_interface {
         ) {
             let message_bytes = message_bytes;
             let binary = binary;
-            let result =
-                messages::assign_dart_signal(message_id, message_bytes, binary);
+            // Negative message IDs are reserved for system signals
+            let result = if message_id < 0 {
+                $crate::assign_system_signal(message_id, message_bytes, binary)
+            } else {
+                messages::assign_dart_signal(message_id, message_bytes, binary)
+            };
             if let Err(error) = result {
                 rinf::debug_print!("{error}");
             }
@@ -0,0 +1,184 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Counters describing the traffic of a channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelStats {
+    /// Number of messages sent into the channel.
+    pub sent: usize,
+    /// Number of messages taken out of the channel by receivers.
+    pub received: usize,
+    /// Number of messages currently waiting in the channel.
+    /// In a broadcast channel, this is the number of messages
+    /// that the slowest receiver has yet to receive.
+    pub depth: usize,
+    /// The largest depth the channel has ever reached.
+    pub high_water: usize,
+    /// Number of messages discarded by the channel policy.
+    pub dropped: usize,
+    /// Number of times an active receiver was replaced by a new one.
+    /// The first receiver cloned from the original one is not counted.
+    pub superseded: usize,
+}
+
+/// This is a special message ID for channels that merge
+/// all Dart signals of a `.proto` file,
+/// which are told apart by their names in snapshots.
+pub const MERGED_SIGNAL_ID: i32 = -5;
+
+/// Statistics of a registered channel at the moment of the snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelSnapshot {
+    pub message_id: i32,
+    pub name: &'static str,
+    pub stats: ChannelStats,
+}
+
+/// Atomic counters that are updated as messages flow through a channel.
+/// Depth and dropped counts are provided by the channel itself.
+#[derive(Default)]
+pub struct ChannelCounters {
+    sent: AtomicUsize,
+    received: AtomicUsize,
+    high_water: AtomicUsize,
+    superseded: AtomicUsize,
+}
+
+impl ChannelCounters {
+    /// Counts a sent message, given the depth after it was enqueued.
+    pub fn record_sent(&self, depth: usize) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.high_water.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    /// Counts a received message.
+    pub fn record_received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a receiver that was replaced by a new one.
+    pub fn record_superseded(&self) {
+        self.superseded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sent(&self) -> usize {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn received(&self) -> usize {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    /// Combines the counters with the state of the channel.
+    pub fn stats(&self, depth: usize, dropped: usize) -> ChannelStats {
+        ChannelStats {
+            sent: self.sent(),
+            received: self.received(),
+            depth,
+            high_water: self.high_water.load(Ordering::Relaxed).max(depth),
+            dropped,
+            superseded: self.superseded.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A channel whose statistics can be inspected
+/// and whose messages can be discarded
+/// without knowing its message type.
+pub trait InspectChannel: Send + Sync {
+    fn stats(&self) -> ChannelStats;
+    /// Discards the messages left from the previous Dart session.
+    fn reset(&self);
+}
+
+/// A channel in the registry. The registry doesn't keep
+/// the channel alive, so dropped channels disappear from snapshots.
+struct RegisteredChannel {
+    message_id: i32,
+    name: &'static str,
+    channel: Weak<dyn InspectChannel>,
+}
+
+static CHANNEL_REGISTRY: Mutex<Vec<RegisteredChannel>> = Mutex::new(Vec::new());
+
+static RESET_CALLBACKS: Mutex<Vec<fn()>> = Mutex::new(Vec::new());
+
+/// Adds a channel to the registry so that it appears in snapshots.
+pub fn register_channel(
+    message_id: i32,
+    name: &'static str,
+    channel: Weak<dyn InspectChannel>,
+) {
+    let mut guard = match CHANNEL_REGISTRY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.retain(|registered| registered.channel.strong_count() > 0);
+    guard.push(RegisteredChannel {
+        message_id,
+        name,
+        channel,
+    });
+}
+
+/// Discards the messages in every live Dart signal channel,
+/// so that a new Dart session doesn't receive messages
+/// sent by the previous one.
+pub fn reset_channels() {
+    let mut guard = match CHANNEL_REGISTRY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.retain(|registered| registered.channel.strong_count() > 0);
+    for registered in guard.iter() {
+        if let Some(channel) = registered.channel.upgrade() {
+            channel.reset();
+        }
+    }
+    drop(guard);
+
+    let callbacks = match RESET_CALLBACKS.lock() {
+        Ok(inner) => inner.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    for callback in callbacks {
+        callback();
+    }
+}
+
+/// Adds a function that is called whenever channels are reset,
+/// so that generated code can clear its own state
+/// before a new Dart session starts.
+#[doc(hidden)]
+pub fn register_reset(callback: fn()) {
+    let mut guard = match RESET_CALLBACKS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.push(callback);
+}
+
+/// Collects the statistics of every live Dart signal channel,
+/// ordered by message ID.
+/// Channels are registered when they are first used.
+pub fn channel_snapshot() -> Vec<ChannelSnapshot> {
+    let mut guard = match CHANNEL_REGISTRY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.retain(|registered| registered.channel.strong_count() > 0);
+    let mut snapshots: Vec<ChannelSnapshot> = guard
+        .iter()
+        .filter_map(|registered| {
+            let channel: Arc<dyn InspectChannel> =
+                registered.channel.upgrade()?;
+            Some(ChannelSnapshot {
+                message_id: registered.message_id,
+                name: registered.name,
+                stats: channel.stats(),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| snapshot.message_id);
+    snapshots
+}
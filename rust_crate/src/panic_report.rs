@@ -0,0 +1,243 @@
+use crate::interface::send_rust_signal;
+use std::any::Any;
+use std::panic::{set_hook, take_hook, Location};
+use std::process::abort;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread;
+
+#[cfg(not(target_family = "wasm"))]
+use std::path::PathBuf;
+#[cfg(not(target_family = "wasm"))]
+use std::sync::atomic::AtomicUsize;
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// This is a special message ID for panic reports.
+pub const RUST_PANIC_ID: i32 = -4;
+
+/// Decides how the panic hook of Rinf handles panics.
+/// Panic hooks that were installed before Rinf started
+/// are called in any case.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Panics are printed in the Dart console in debug mode.
+    #[default]
+    ForwardToDart,
+    /// Panics are left to the previous hook,
+    /// which is the default one that prints to `stderr`
+    /// unless another one was installed.
+    LogOnly,
+    /// The whole process is aborted after the panic is reported,
+    /// even if it occurred inside a spawned task.
+    Abort,
+}
+
+static PANIC_POLICY: Mutex<PanicPolicy> =
+    Mutex::new(PanicPolicy::ForwardToDart);
+
+static PANIC_REPORTS: AtomicBool = AtomicBool::new(false);
+
+/// Sets how the panic hook of Rinf handles panics.
+/// Panic reports and crash files are produced
+/// regardless of this policy, if enabled.
+pub fn set_panic_policy(policy: PanicPolicy) {
+    let mut guard = match PANIC_POLICY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard = policy;
+}
+
+fn panic_policy() -> PanicPolicy {
+    let guard = match PANIC_POLICY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard
+}
+
+#[cfg(not(target_family = "wasm"))]
+static CRASH_DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Number of crash files written so far,
+/// which keeps file names unique within the same millisecond.
+#[cfg(not(target_family = "wasm"))]
+static CRASH_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets whether panics are reported to Dart,
+/// including those inside spawned tasks.
+/// Unlike the messages printed by `debug_print!`,
+/// reports are also sent in release mode.
+/// If the `show-backtrace` feature is enabled,
+/// reports on native platforms include a symbolicated backtrace.
+pub fn set_panic_reports(enabled: bool) {
+    PANIC_REPORTS.store(enabled, Ordering::SeqCst);
+}
+
+/// Sets the directory where a crash file is written for each panic,
+/// so that the report survives even if the app is closed.
+/// Files are named `rust-panic-{timestamp}-{sequence}.json`,
+/// with the timestamp in milliseconds since the Unix epoch
+/// and a sequence number that increases with each crash file
+/// written by this process.
+/// The directory should already exist.
+/// `None` stops writing crash files, which is the default.
+#[cfg(not(target_family = "wasm"))]
+pub fn set_crash_directory(directory: Option<PathBuf>) {
+    let mut guard = match CRASH_DIRECTORY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard = directory;
+}
+
+/// Installs the panic hook of Rinf,
+/// which prints panics and reports them if enabled.
+/// The hook is installed only once, even after hot restarts,
+/// and it calls the hook that was installed before it.
+pub fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous_hook = take_hook();
+        set_hook(Box::new(move |panic_info| {
+            let message = panic_message(panic_info.payload());
+            report_panic(&message, panic_info.location());
+            let policy = panic_policy();
+            if policy != PanicPolicy::LogOnly {
+                print_panic(&panic_info.to_string());
+            }
+            previous_hook(panic_info);
+            if policy == PanicPolicy::Abort {
+                abort();
+            }
+        }));
+    });
+}
+
+/// Extracts the message given to `panic!`, if any.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Unknown panic payload")
+    }
+}
+
+/// Prints the panic in the Dart console in debug mode,
+/// with a backtrace if the `show-backtrace` feature is enabled.
+fn print_panic(panic_info: &str) {
+    #[cfg(not(feature = "backtrace"))]
+    {
+        crate::debug_print!("A panic occurred in Rust.\n{panic_info}");
+    }
+    #[cfg(feature = "backtrace")]
+    {
+        let backtrace = capture_backtrace().unwrap_or_default();
+        crate::debug_print!(
+            "A panic occurred in Rust.\n{panic_info}\n{backtrace}"
+        );
+    }
+}
+
+/// Sends a panic report to Dart and writes it to a crash file,
+/// depending on the settings.
+fn report_panic(message: &str, location: Option<&Location>) {
+    let send_to_dart = PANIC_REPORTS.load(Ordering::SeqCst);
+    #[cfg(not(target_family = "wasm"))]
+    let crash_directory = match CRASH_DIRECTORY.lock() {
+        Ok(inner) => inner.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    #[cfg(target_family = "wasm")]
+    let crash_directory: Option<()> = None;
+    if !send_to_dart && crash_directory.is_none() {
+        return;
+    }
+
+    let report = encode_report(message, location);
+    if send_to_dart {
+        let bytes = report.clone().into_bytes();
+        if let Err(error) = send_rust_signal(RUST_PANIC_ID, Vec::new(), bytes) {
+            eprintln!("{error}");
+        }
+    }
+    #[cfg(not(target_family = "wasm"))]
+    if let Some(directory) = crash_directory {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        let sequence = CRASH_FILE_COUNT.fetch_add(1, Ordering::SeqCst);
+        let file_name = format!("rust-panic-{timestamp}-{sequence}.json");
+        let path = directory.join(file_name);
+        if let Err(error) = std::fs::write(&path, report) {
+            eprintln!("Could not write the crash file {path:?}: {error}");
+        }
+    }
+}
+
+/// Encodes the panic as a JSON object.
+fn encode_report(message: &str, location: Option<&Location>) -> String {
+    let (file, line, column) = match location {
+        Some(location) => (
+            json_string(location.file()),
+            location.line().to_string(),
+            location.column().to_string(),
+        ),
+        None => (
+            String::from("null"),
+            String::from("null"),
+            String::from("null"),
+        ),
+    };
+    let thread = match thread::current().name() {
+        Some(name) => json_string(name),
+        None => String::from("null"),
+    };
+    let backtrace = match capture_backtrace() {
+        Some(backtrace) => json_string(&backtrace),
+        None => String::from("null"),
+    };
+    format!(
+        "{{\"message\":{},\"file\":{file},\"line\":{line},\
+        \"column\":{column},\"thread\":{thread},\"backtrace\":{backtrace}}}",
+        json_string(message),
+    )
+}
+
+/// Captures a symbolicated backtrace of the current thread,
+/// which is only available with the `show-backtrace` feature.
+fn capture_backtrace() -> Option<String> {
+    #[cfg(all(feature = "backtrace", not(target_family = "wasm")))]
+    {
+        Some(format!("{:?}", backtrace::Backtrace::new()))
+    }
+    #[cfg(not(all(feature = "backtrace", not(target_family = "wasm"))))]
+    {
+        None
+    }
+}
+
+/// Quotes and escapes text so that it can be used as a JSON string.
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
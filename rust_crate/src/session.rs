@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of times the Rust logic has been started in this process.
+static SESSION_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Describes how the current Rust logic was started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartKind {
+    /// The Rust logic has not been started yet in this process.
+    NotStarted,
+    /// The Rust logic was started for the first time in this process.
+    ColdStart,
+    /// The Rust logic was started again in the same process,
+    /// such as after Dart's hot restart or when the Flutter engine
+    /// is attached again on Android,
+    /// so statics from the previous session may still hold values.
+    Restart,
+}
+
+/// Information about the current session of the Rust logic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// The ID of the session, starting from 1 and
+    /// increasing every time the Rust logic is started.
+    /// This is 0 if the Rust logic has not been started yet.
+    pub id: usize,
+    /// How the Rust logic was started in this session.
+    pub start_kind: StartKind,
+}
+
+/// Returns the ID of the current session, which starts from 1 and
+/// increases every time Dart starts the Rust logic again,
+/// such as after a hot restart.
+/// This returns 0 if the Rust logic has not been started yet.
+pub fn session_id() -> usize {
+    SESSION_ID.load(Ordering::SeqCst)
+}
+
+/// Returns information about the current session.
+/// This is useful for deciding whether long-lived resources
+/// held in statics should be reused or torn down.
+pub fn session_info() -> SessionInfo {
+    let id = session_id();
+    let start_kind = match id {
+        0 => StartKind::NotStarted,
+        1 => StartKind::ColdStart,
+        _ => StartKind::Restart,
+    };
+    SessionInfo { id, start_kind }
+}
+
+/// Marks the beginning of a new session, returning its ID.
+pub fn begin_session() -> usize {
+    SESSION_ID.fetch_add(1, Ordering::SeqCst) + 1
+}
@@ -1,12 +1,22 @@
+use crate::session::session_id;
+use crate::time::{sleep, Sleep};
+use std::cmp::Reverse;
+use std::fmt::Display;
 use std::future::Future;
+use std::mem::take;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::pin::Pin;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, Weak};
 use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
 // Thread-blocking operations are possible
 // only on non-web platforms.
 #[cfg(not(target_family = "wasm"))]
 use std::sync::Condvar;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
 
 type ShutdownEventsLock = LazyLock<ShutdownEvents>;
 pub static SHUTDOWN_EVENTS: ShutdownEventsLock =
@@ -29,14 +39,579 @@ pub struct ShutdownEvents {
 /// Awaiting this receiver in the async main Rust function
 /// is necessary to prevent the async runtime in Rust from
 /// finishing immediately.
+/// Once Dart has stopped, this runs the shutdown hooks
+/// registered in the current session before completing.
+/// If several of these futures are awaited, the hooks run only once
+/// and every future completes after all of them have finished.
 pub fn dart_shutdown() -> impl Future<Output = ()> {
-    SHUTDOWN_EVENTS.dart_stopped.wait_async()
+    let session = session_id();
+    let dart_stopped = SHUTDOWN_EVENTS.dart_stopped.wait_async();
+    async move {
+        dart_stopped.await;
+        let (hooks_done, is_first) = claim_hook_run(session);
+        if is_first {
+            // Others are released even if this future is dropped midway
+            let _guard = HookRunGuard(hooks_done);
+            run_shutdown_hooks(session).await;
+        } else {
+            hooks_done.wait_async().await;
+        }
+    }
+}
+
+/// Completion events of the hook runs, one for each session
+/// whose hooks are running.
+static HOOK_RUNS: Mutex<Vec<(usize, Arc<Event>)>> = Mutex::new(Vec::new());
+
+/// Returns the event that is set when the hooks of the session
+/// have finished, along with whether the caller should run them.
+fn claim_hook_run(session: usize) -> (Arc<Event>, bool) {
+    let mut guard = match HOOK_RUNS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    // Finished runs are forgotten, as their hooks are already taken
+    guard.retain(|(_, hooks_done)| !hooks_done.is_set());
+    if let Some((_, hooks_done)) = guard
+        .iter()
+        .find(|(run_session, _)| *run_session == session)
+    {
+        return (hooks_done.clone(), false);
+    }
+    let hooks_done = Arc::new(Event::new());
+    guard.push((session, hooks_done.clone()));
+    (hooks_done, true)
+}
+
+/// Marks a hook run as finished when dropped.
+struct HookRunGuard(Arc<Event>);
+
+impl Drop for HookRunGuard {
+    fn drop(&mut self) {
+        self.0.set();
+    }
+}
+
+static SHUTDOWN_GRACE_PERIOD: Mutex<Option<Duration>> =
+    Mutex::new(Some(Duration::from_secs(10)));
+
+/// Sets how long the app waits for the Rust logic to finish
+/// after Dart has stopped. The default is 10 seconds,
+/// and `None` makes the app wait indefinitely.
+/// The timeouts of the shutdown hooks registered in the current session
+/// are added to the grace period, so that hooks are never cut short.
+/// When the grace period expires, tracked tasks that are still alive
+/// are reported and the app closes without waiting any further.
+/// This has no effect on the web,
+/// where the app never waits for Rust to finish.
+pub fn set_shutdown_grace_period(grace_period: Option<Duration>) {
+    let mut guard = match SHUTDOWN_GRACE_PERIOD.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard = grace_period;
+}
+
+/// Returns how long the app waits for the Rust logic to finish
+/// after Dart has stopped, including the time
+/// that the pending shutdown hooks are allowed to run.
+/// This should be called before the hooks are taken.
+#[cfg(not(target_family = "wasm"))]
+pub fn shutdown_grace_period() -> Option<Duration> {
+    let grace_period = {
+        let guard = match SHUTDOWN_GRACE_PERIOD.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        (*guard)?
+    };
+    let session = session_id();
+    let guard = match SHUTDOWN_HOOKS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let hook_timeouts = guard
+        .iter()
+        .filter(|shutdown_hook| shutdown_hook.session == session)
+        .fold(Duration::ZERO, |total, shutdown_hook| {
+            total.saturating_add(shutdown_hook.timeout)
+        });
+    Some(grace_period.saturating_add(hook_timeouts))
+}
+
+/// The duration that each shutdown hook is allowed to run
+/// when registered with `on_shutdown`.
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(not(target_family = "wasm"))]
+type HookFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+#[cfg(target_family = "wasm")]
+type HookFuture = Pin<Box<dyn Future<Output = Result<(), String>>>>;
+
+#[cfg(not(target_family = "wasm"))]
+type HookFn = Box<dyn FnOnce() -> HookFuture + Send>;
+#[cfg(target_family = "wasm")]
+type HookFn = Box<dyn FnOnce() -> HookFuture>;
+
+/// A cleanup task that runs once when Dart stops.
+struct ShutdownHook {
+    session: usize, // The session that registered this hook
+    priority: i32,
+    timeout: Duration,
+    hook: HookFn,
+}
+
+#[cfg(not(target_family = "wasm"))]
+static SHUTDOWN_HOOKS: Mutex<Vec<ShutdownHook>> = Mutex::new(Vec::new());
+
+// Hooks on the web are not `Send`,
+// so they are kept on the only JavaScript thread.
+#[cfg(target_family = "wasm")]
+thread_local! {
+    static SHUTDOWN_HOOKS: std::cell::RefCell<Vec<ShutdownHook>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Registers an async cleanup task that runs after Dart stops,
+/// before `dart_shutdown` completes.
+/// Hooks with a higher priority run first, and hooks with the same
+/// priority run in the order they were registered.
+/// Each hook is allowed to run for 5 seconds,
+/// and a hook that fails or times out is reported.
+#[cfg(not(target_family = "wasm"))]
+pub fn on_shutdown<F, Fut, E>(priority: i32, hook: F)
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    on_shutdown_with_timeout(priority, DEFAULT_HOOK_TIMEOUT, hook);
+}
+
+/// Registers an async cleanup task that runs after Dart stops,
+/// before `dart_shutdown` completes.
+/// The hook is abandoned and reported if it doesn't finish
+/// within the given timeout.
+#[cfg(not(target_family = "wasm"))]
+pub fn on_shutdown_with_timeout<F, Fut, E>(
+    priority: i32,
+    timeout: Duration,
+    hook: F,
+) where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    let shutdown_hook = ShutdownHook {
+        session: session_id(),
+        priority,
+        timeout,
+        hook: Box::new(move || {
+            let future = hook();
+            Box::pin(
+                async move { future.await.map_err(|error| error.to_string()) },
+            )
+        }),
+    };
+    let mut guard = match SHUTDOWN_HOOKS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.push(shutdown_hook);
+}
+
+/// Registers an async cleanup task that runs after Dart stops,
+/// before `dart_shutdown` completes.
+/// Hooks with a higher priority run first, and hooks with the same
+/// priority run in the order they were registered.
+/// Each hook is allowed to run for 5 seconds,
+/// and a hook that fails or times out is reported.
+#[cfg(target_family = "wasm")]
+pub fn on_shutdown<F, Fut, E>(priority: i32, hook: F)
+where
+    F: FnOnce() -> Fut + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: Display,
+{
+    on_shutdown_with_timeout(priority, DEFAULT_HOOK_TIMEOUT, hook);
+}
+
+/// Registers an async cleanup task that runs after Dart stops,
+/// before `dart_shutdown` completes.
+/// The hook is abandoned and reported if it doesn't finish
+/// within the given timeout.
+#[cfg(target_family = "wasm")]
+pub fn on_shutdown_with_timeout<F, Fut, E>(
+    priority: i32,
+    timeout: Duration,
+    hook: F,
+) where
+    F: FnOnce() -> Fut + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: Display,
+{
+    let shutdown_hook = ShutdownHook {
+        session: session_id(),
+        priority,
+        timeout,
+        hook: Box::new(move || {
+            let future = hook();
+            Box::pin(
+                async move { future.await.map_err(|error| error.to_string()) },
+            )
+        }),
+    };
+    SHUTDOWN_HOOKS.with(|hooks| hooks.borrow_mut().push(shutdown_hook));
+}
+
+/// Takes the hooks registered in the given session
+/// so that each of them runs only once.
+fn take_shutdown_hooks(session: usize) -> Vec<ShutdownHook> {
+    #[cfg(not(target_family = "wasm"))]
+    let hooks = {
+        let mut guard = match SHUTDOWN_HOOKS.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        split_hooks(&mut guard, session)
+    };
+    #[cfg(target_family = "wasm")]
+    let hooks = SHUTDOWN_HOOKS
+        .with(|hooks| split_hooks(&mut hooks.borrow_mut(), session));
+    hooks
+}
+
+/// Removes the hooks of the given session from the list.
+/// Hooks of newer sessions are left for their own shutdown,
+/// while hooks of older sessions that never ran are discarded.
+fn split_hooks(
+    hooks: &mut Vec<ShutdownHook>,
+    session: usize,
+) -> Vec<ShutdownHook> {
+    let (taken, kept) = take(hooks)
+        .into_iter()
+        .filter(|shutdown_hook| shutdown_hook.session >= session)
+        .partition(|shutdown_hook| shutdown_hook.session == session);
+    *hooks = kept;
+    taken
+}
+
+/// Runs the hooks of the given session one by one
+/// in the order of priority.
+async fn run_shutdown_hooks(session: usize) {
+    let mut hooks = take_shutdown_hooks(session);
+    // The sort is stable, so registration order is kept within a priority
+    hooks.sort_by_key(|shutdown_hook| Reverse(shutdown_hook.priority));
+    for shutdown_hook in hooks {
+        let priority = shutdown_hook.priority;
+        let timeout = shutdown_hook.timeout;
+        let hook_run = HookRun {
+            future: Some((shutdown_hook.hook)()),
+            deadline: sleep(timeout),
+        };
+        // Dart has stopped and reports should also appear in release mode,
+        // so failures go to the console.
+        match hook_run.await {
+            HookOutcome::Completed => {}
+            HookOutcome::Failed(error) => {
+                eprintln!(
+                    "Shutdown hook with priority {priority} failed.\n{error}"
+                );
+            }
+            HookOutcome::Panicked => {
+                eprintln!("Shutdown hook with priority {priority} panicked.");
+            }
+            HookOutcome::TimedOut => {
+                eprintln!(
+                    "Shutdown hook with priority {priority} \
+                    did not finish within {timeout:?}."
+                );
+            }
+        }
+    }
+}
+
+/// How a shutdown hook has finished.
+enum HookOutcome {
+    Completed,
+    Failed(String),
+    Panicked,
+    TimedOut,
+}
+
+/// A future that runs a shutdown hook until it finishes
+/// or its timeout has passed.
+struct HookRun {
+    future: Option<HookFuture>,
+    deadline: Sleep,
+}
+
+impl Future for HookRun {
+    type Output = HookOutcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let Some(future) = this.future.as_mut() else {
+            return Poll::Ready(HookOutcome::Completed);
+        };
+        // A panicking hook should not prevent other hooks from running
+        let polled =
+            catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx)));
+        match polled {
+            Ok(Poll::Ready(Ok(()))) => {
+                this.future = None;
+                return Poll::Ready(HookOutcome::Completed);
+            }
+            Ok(Poll::Ready(Err(error))) => {
+                this.future = None;
+                return Poll::Ready(HookOutcome::Failed(error));
+            }
+            Ok(Poll::Pending) => {}
+            Err(_) => {
+                this.future = None;
+                return Poll::Ready(HookOutcome::Panicked);
+            }
+        }
+        if Pin::new(&mut this.deadline).poll(cx).is_ready() {
+            this.future = None;
+            return Poll::Ready(HookOutcome::TimedOut);
+        }
+        Poll::Pending
+    }
+}
+
+static SHUTDOWN_TOKEN: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+/// Returns the root cancellation token of the current Dart session.
+/// The token is cancelled automatically when Dart stops,
+/// and a new root token is created for the next session.
+/// Child tokens derived from it can scope cancellation
+/// to a screen or an operation.
+pub fn shutdown_token() -> CancellationToken {
+    let session = SHUTDOWN_EVENTS.dart_stopped.set_count();
+    let mut guard = match SHUTDOWN_TOKEN.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match guard.as_ref() {
+        Some(token) if token.node.session == Some(session) => token.clone(),
+        _ => {
+            let token = CancellationToken::with_session(Some(session));
+            guard.replace(token.clone());
+            token
+        }
+    }
+}
+
+/// A token that tells tasks to stop what they are doing.
+/// Cancelling a token also cancels all of its child tokens,
+/// while cancelling a child token doesn't affect its parent.
+/// This doesn't rely on any specific async runtime.
+#[derive(Clone)]
+pub struct CancellationToken {
+    node: Arc<TokenNode>,
+}
+
+/// The state of a token shared by its clones.
+struct TokenNode {
+    // The Dart session whose end cancels this token, if any
+    session: Option<usize>,
+    state: Mutex<TokenState>,
+}
+
+struct TokenState {
+    cancelled: bool,
+    last_waiter_id: usize,
+    wakers: Vec<(usize, Waker)>, // Wakers of waiting tasks by ID
+    children: Vec<Weak<TokenNode>>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is cancelled only by calling `cancel`.
+    pub fn new() -> Self {
+        Self::with_session(None)
+    }
+
+    fn with_session(session: Option<usize>) -> Self {
+        CancellationToken {
+            node: Arc::new(TokenNode {
+                session,
+                state: Mutex::new(TokenState {
+                    cancelled: false,
+                    last_waiter_id: 0,
+                    wakers: Vec::new(),
+                    children: Vec::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Creates a token that is cancelled along with this one.
+    pub fn child_token(&self) -> Self {
+        let child = Self::with_session(self.node.session);
+        let mut guard = self.node.lock();
+        if guard.cancelled {
+            child.node.lock().cancelled = true;
+        } else {
+            guard.children.retain(|node| node.strong_count() > 0);
+            guard.children.push(Arc::downgrade(&child.node));
+        }
+        child
+    }
+
+    /// Cancels this token and all of its child tokens,
+    /// waking up every task waiting on them.
+    pub fn cancel(&self) {
+        self.node.cancel();
+    }
+
+    /// Checks whether this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.node.is_cancelled()
+    }
+
+    /// Creates a future that will be resolved
+    /// when this token is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> {
+        let waiter_id = {
+            let mut guard = self.node.lock();
+            guard.last_waiter_id += 1;
+            guard.last_waiter_id
+        };
+        CancelledFuture {
+            node: self.node.clone(),
+            waiter_id,
+            dart_stopped: self
+                .node
+                .session
+                .map(|_| SHUTDOWN_EVENTS.dart_stopped.wait_async()),
+        }
+    }
+
+    /// Runs the future until it completes or this token is cancelled.
+    /// Returns `None` if the token was cancelled first.
+    pub async fn run_until_cancelled<F: Future>(
+        &self,
+        future: F,
+    ) -> Option<F::Output> {
+        let mut future = std::pin::pin!(future);
+        let mut cancelled = std::pin::pin!(self.cancelled());
+        std::future::poll_fn(|cx| {
+            if cancelled.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            future.as_mut().poll(cx).map(Some)
+        })
+        .await
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenNode {
+    /// Locks the token state. This method does not fail if the mutex
+    /// is poisoned but simply ignores the failure.
+    fn lock(&self) -> MutexGuard<'_, TokenState> {
+        match self.state.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn cancel(&self) {
+        let (wakers, children) = {
+            let mut guard = self.lock();
+            if guard.cancelled {
+                return;
+            }
+            guard.cancelled = true;
+            (take(&mut guard.wakers), take(&mut guard.children))
+        };
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+        for child in children {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        // The Dart session has ended if the event was set in the meantime
+        if let Some(session) = self.session {
+            let dart_stopped = &SHUTDOWN_EVENTS.dart_stopped;
+            if dart_stopped.set_count() != session || dart_stopped.is_set() {
+                return true;
+            }
+        }
+        self.lock().cancelled
+    }
+}
+
+/// Future that resolves when a `CancellationToken` is cancelled.
+struct CancelledFuture {
+    node: Arc<TokenNode>,
+    waiter_id: usize,
+    dart_stopped: Option<EventFuture>, // Present for session tokens
+}
+
+impl Future for CancelledFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.node.is_cancelled() {
+            return Poll::Ready(());
+        }
+        {
+            let mut guard = this.node.lock();
+            if guard.cancelled {
+                return Poll::Ready(());
+            }
+            let waker = cx.waker();
+            match guard
+                .wakers
+                .iter_mut()
+                .find(|(id, _)| *id == this.waiter_id)
+            {
+                Some((_, existing)) => existing.clone_from(waker),
+                None => guard.wakers.push((this.waiter_id, waker.clone())),
+            }
+        }
+        // Wake up the task when Dart stops as well
+        if let Some(dart_stopped) = this.dart_stopped.as_mut() {
+            if Pin::new(dart_stopped).poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for CancelledFuture {
+    /// Removes the waker so that it doesn't occupy memory in the token.
+    fn drop(&mut self) {
+        let mut guard = self.node.lock();
+        guard.wakers.retain(|(id, _)| *id != self.waiter_id);
+    }
 }
 
 /// Synchronization primitive that allows
 /// threads or async tasks to wait until a condition is met.
+/// It can be used to notify other threads or async tasks
+/// regardless of which async runtime they are on.
+///
+/// By default, the flag stays `true` after being set
+/// and every waiter is released until the flag is cleared.
+/// In auto-reset mode, each `set` releases only one waiter,
+/// and the flag is cleared as soon as that waiter passes.
 pub struct Event {
-    inner: Arc<Mutex<EventInner>>,
+    inner: Arc<EventInner>,
     #[cfg(not(target_family = "wasm"))]
     condvar: Arc<Condvar>,
 }
@@ -44,43 +619,66 @@ pub struct Event {
 impl Event {
     /// Creates a new `Event` with the initial flag state.
     pub fn new() -> Self {
+        Self::with_auto_reset(false)
+    }
+
+    /// Creates a new `Event` in auto-reset mode,
+    /// where each `set` releases only one waiter.
+    pub fn new_auto_reset() -> Self {
+        Self::with_auto_reset(true)
+    }
+
+    fn with_auto_reset(auto_reset: bool) -> Self {
         Event {
-            inner: Arc::new(Mutex::new(EventInner::new())),
+            inner: Arc::new(EventInner::new(auto_reset)),
             #[cfg(not(target_family = "wasm"))]
             condvar: Arc::new(Condvar::new()),
         }
     }
 
-    /// Creates a future that will be resolved
-    /// when the flag is set to `true`.
-    pub fn wait_async(&self) -> EventFuture {
-        let guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        EventFuture {
-            started_session: guard.session,
-            inner: self.inner.clone(),
-        }
+    /// Returns the number of times the flag has been set.
+    /// Comparing this number tells whether
+    /// the event has been set in the meantime.
+    pub fn set_count(&self) -> usize {
+        self.inner.session.load(Ordering::SeqCst)
+    }
+
+    /// Checks whether the flag is currently set to `true`.
+    pub fn is_set(&self) -> bool {
+        self.inner.flag.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of async tasks that are currently waiting.
+    /// Futures stop waiting when they are dropped,
+    /// even if the flag has not been set.
+    pub fn waiter_count(&self) -> usize {
+        self.inner.lock().len()
     }
-}
 
-#[cfg(not(target_family = "wasm"))]
-impl Event {
     /// Sets the flag to `true` and notifies all waiting threads.
     /// This will wake up any threads or async tasks.
+    /// In auto-reset mode, only one of them is released.
     pub fn set(&self) {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        guard.flag = true; // Set the flag
-        guard.session += 1; // Increment the session count
+        // The state is changed while holding the lock
+        // so that waiters cannot miss it between checking and sleeping
+        let mut guard = self.inner.lock();
+        self.inner.flag.store(true, Ordering::SeqCst); // Set the flag
+        self.inner.session.fetch_add(1, Ordering::SeqCst); // Count the set
 
-        // Wake all threads and async tasks when the event is set
-        self.condvar.notify_all();
-        for waker in guard.wakers.drain(..) {
-            waker.wake();
+        // Wake a single waiter in auto-reset mode,
+        // and every thread and async task otherwise
+        if self.inner.auto_reset {
+            #[cfg(not(target_family = "wasm"))]
+            self.condvar.notify_one();
+            if let Some(waker) = guard.pop() {
+                waker.wake();
+            }
+        } else {
+            #[cfg(not(target_family = "wasm"))]
+            self.condvar.notify_all();
+            for waker in guard.drain() {
+                waker.wake();
+            }
         }
     }
 
@@ -89,13 +687,50 @@ impl Event {
     /// but subsequent calls to `wait` will
     /// block until the flag is set again.
     pub fn clear(&self) {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        guard.flag = false; // Clear the flag
+        let _guard = self.inner.lock();
+        self.inner.flag.store(false, Ordering::SeqCst); // Clear the flag
+    }
+
+    /// Creates a future that will be resolved
+    /// when the flag is set to `true`.
+    pub fn wait_async(&self) -> EventFuture {
+        EventFuture {
+            started_session: self.inner.session.load(Ordering::SeqCst),
+            inner: self.inner.clone(),
+            waiter_key: None,
+            registered_waker: None,
+        }
     }
 
+    /// Creates a future that will be resolved
+    /// when the flag is set to `true` or the timeout has passed.
+    /// The output is `true` if the flag was set in time.
+    /// Unlike `wait_timeout`, this also works on the web.
+    pub fn wait_async_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = bool> {
+        let mut event_future = self.wait_async();
+        let mut deadline = sleep(timeout);
+        std::future::poll_fn(move |cx| {
+            if Pin::new(&mut event_future).poll(cx).is_ready() {
+                return Poll::Ready(true);
+            }
+            Pin::new(&mut deadline).poll(cx).map(|_| false)
+        })
+    }
+}
+
+impl Default for Event {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Thread-blocking operations are possible
+// only on non-web platforms.
+#[cfg(not(target_family = "wasm"))]
+impl Event {
     /// Blocks the current thread until the flag is set to `true`.
     /// If the flag is already set,
     /// this method will return immediately.
@@ -105,56 +740,202 @@ impl Event {
             EventBlocking::new(self.inner.clone(), self.condvar.clone());
         event_blocking.wait();
     }
+
+    /// Blocks the current thread until the flag is set to `true`
+    /// or the timeout has passed.
+    /// Returns `true` if the flag was set in time.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let event_blocking =
+            EventBlocking::new(self.inner.clone(), self.condvar.clone());
+        event_blocking.wait_timeout(timeout)
+    }
 }
 
 /// Internal state for the `Event` synchronization primitive.
+/// The flag and the session count are atomic so that they can be
+/// read without locking, but they are only changed
+/// while the waiters are locked.
 struct EventInner {
-    flag: bool,         // Current flag state
-    session: usize,     // Session count to detect changes
-    wakers: Vec<Waker>, // List of wakers to be notified
+    flag: AtomicBool,           // Current flag state
+    session: AtomicUsize,       // Session count to detect changes
+    auto_reset: bool,           // Whether each set releases only one waiter
+    waiters: Mutex<WaiterSlab>, // Wakers of waiting async tasks
 }
 
 impl EventInner {
-    fn new() -> Self {
+    fn new(auto_reset: bool) -> Self {
         EventInner {
-            flag: false,
-            session: 0,
-            wakers: Vec::new(),
+            flag: AtomicBool::new(false),
+            session: AtomicUsize::new(0),
+            auto_reset,
+            waiters: Mutex::new(WaiterSlab::new()),
+        }
+    }
+
+    /// Locks the waiters. This method does not fail if the mutex
+    /// is poisoned but simply ignores the failure.
+    fn lock(&self) -> MutexGuard<'_, WaiterSlab> {
+        match self.waiters.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
         }
     }
+
+    /// Checks whether a waiter that started in the given session
+    /// can stop waiting. In auto-reset mode,
+    /// the waiter that passes clears the flag,
+    /// so the waiters should be locked.
+    fn try_pass(&self, started_session: usize) -> bool {
+        if self.auto_reset {
+            self.flag.swap(false, Ordering::SeqCst)
+        } else {
+            self.flag.load(Ordering::SeqCst)
+                || self.session.load(Ordering::SeqCst) != started_session
+        }
+    }
+}
+
+/// Identifies a slot in the `WaiterSlab`.
+/// The generation tells whether the slot has been emptied
+/// and possibly reused since the key was given out.
+#[derive(Clone, Copy)]
+struct WaiterKey {
+    index: usize,
+    generation: usize,
+}
+
+/// A slot that may hold the waker of a waiting async task.
+struct WaiterSlot {
+    generation: usize, // Increased whenever the slot is emptied
+    waker: Option<Waker>,
+}
+
+/// Wakers of waiting async tasks, stored in slots so that
+/// each of them can be replaced or removed in constant time.
+struct WaiterSlab {
+    slots: Vec<WaiterSlot>,
+    vacant: Vec<usize>, // Indices of empty slots to be reused
+    len: usize,         // Number of occupied slots
+}
+
+impl WaiterSlab {
+    fn new() -> Self {
+        WaiterSlab {
+            slots: Vec::new(),
+            vacant: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Stores the waker and returns the key of its slot.
+    fn insert(&mut self, waker: Waker) -> WaiterKey {
+        self.len += 1;
+        if let Some(index) = self.vacant.pop() {
+            if let Some(slot) = self.slots.get_mut(index) {
+                slot.waker = Some(waker);
+                return WaiterKey {
+                    index,
+                    generation: slot.generation,
+                };
+            }
+        }
+        self.slots.push(WaiterSlot {
+            generation: 0,
+            waker: Some(waker),
+        });
+        WaiterKey {
+            index: self.slots.len() - 1,
+            generation: 0,
+        }
+    }
+
+    /// Returns the occupied slot that the key refers to, if any.
+    fn get_mut(&mut self, key: WaiterKey) -> Option<&mut WaiterSlot> {
+        self.slots.get_mut(key.index).filter(|slot| {
+            slot.generation == key.generation && slot.waker.is_some()
+        })
+    }
+
+    /// Replaces the waker in the slot,
+    /// unless both would wake the same task.
+    /// Returns `false` if the slot has been emptied in the meantime.
+    fn update(&mut self, key: WaiterKey, waker: &Waker) -> bool {
+        match self.get_mut(key).and_then(|slot| slot.waker.as_mut()) {
+            Some(existing) => {
+                existing.clone_from(waker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Empties the slot so that it can be reused.
+    /// Returns `false` if the slot has been emptied in the meantime.
+    fn remove(&mut self, key: WaiterKey) -> bool {
+        let Some(slot) = self.get_mut(key) else {
+            return false;
+        };
+        slot.waker = None;
+        slot.generation += 1;
+        self.vacant.push(key.index);
+        self.len -= 1;
+        true
+    }
+
+    /// Takes out the waker of any waiting task.
+    fn pop(&mut self) -> Option<Waker> {
+        let index = self.slots.iter().position(|slot| slot.waker.is_some())?;
+        let slot = self.slots.get_mut(index)?;
+        slot.generation += 1;
+        self.vacant.push(index);
+        self.len -= 1;
+        slot.waker.take()
+    }
+
+    /// Takes out every waker, emptying all slots.
+    fn drain(&mut self) -> Vec<Waker> {
+        let mut wakers = Vec::with_capacity(self.len);
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some(waker) = slot.waker.take() {
+                slot.generation += 1;
+                self.vacant.push(index);
+                wakers.push(waker);
+            }
+        }
+        self.len = 0;
+        wakers
+    }
 }
 
 /// Struct to handle waiting with session tracking.
 #[cfg(not(target_family = "wasm"))]
 struct EventBlocking {
-    inner: Arc<Mutex<EventInner>>,
+    inner: Arc<EventInner>,
     condvar: Arc<Condvar>,
     started_session: usize,
 }
 
 #[cfg(not(target_family = "wasm"))]
 impl EventBlocking {
-    fn new(inner: Arc<Mutex<EventInner>>, condvar: Arc<Condvar>) -> Self {
-        let guard = match inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+    fn new(inner: Arc<EventInner>, condvar: Arc<Condvar>) -> Self {
+        let started_session = inner.session.load(Ordering::SeqCst);
         EventBlocking {
-            inner: inner.clone(),
+            inner,
             condvar,
-            started_session: guard.session,
+            started_session,
         }
     }
 
     pub fn wait(&self) {
-        // Lock the inner state and wait on the condition variable
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        // Lock the waiters and wait on the condition variable
+        let mut guard = self.inner.lock();
         loop {
             // Check if the condition is met
-            if guard.flag || guard.session != self.started_session {
+            if self.inner.try_pass(self.started_session) {
                 break;
             }
             // Wait on the condition variable and reassign the guard
@@ -164,42 +945,106 @@ impl EventBlocking {
             };
         }
     }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.inner.lock();
+        loop {
+            if self.inner.try_pass(self.started_session) {
+                return true;
+            }
+            // Spurious wakeups shouldn't extend the total waiting time
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            guard = match self.condvar.wait_timeout(guard, remaining) {
+                Ok((inner, _)) => inner,
+                Err(poisoned) => poisoned.into_inner().0,
+            };
+        }
+    }
 }
 
 /// Future that resolves when the `Event` flag is set to `true`.
 pub struct EventFuture {
     started_session: usize,
-    inner: Arc<Mutex<EventInner>>, // Use the combined inner state
+    inner: Arc<EventInner>,
+    waiter_key: Option<WaiterKey>, // Slot of the registered waker
+    // Kept to tell whether the slot holds the waker of the current task
+    registered_waker: Option<Waker>,
 }
 
 impl Future for EventFuture {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut guard = match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        };
+        let this = self.get_mut();
+        let waker = cx.waker();
+
+        // Avoid locking when the same task polls again,
+        // which is common for futures kept inside receivers.
+        // If the event was set in the meantime,
+        // the atomic state tells so even without the lock.
+        // This doesn't apply to auto-reset mode,
+        // where passing takes the flag.
+        if !this.inner.auto_reset && this.waiter_key.is_some() {
+            let is_same_task = this
+                .registered_waker
+                .as_ref()
+                .is_some_and(|registered| registered.will_wake(waker));
+            if is_same_task && !this.inner.try_pass(this.started_session) {
+                return Poll::Pending;
+            }
+        }
+
+        let mut guard = this.inner.lock();
 
         // Check if the flag is set or if the session count has changed.
         // If the flag is true or the session count is different
         // because a new event session has started, stop polling.
-        if guard.flag || guard.session != self.started_session {
-            Poll::Ready(())
-        } else {
-            // Check if the current waker is already in the list of wakers.
-            // If the waker is unique (not already in the list),
-            // add it to the list.
-            let waker = cx.waker();
-            if !guard
-                .wakers
-                .iter()
-                .any(|existing_waker| existing_waker.will_wake(waker))
-            {
-                guard.wakers.push(waker.clone());
+        if this.inner.try_pass(this.started_session) {
+            if let Some(key) = this.waiter_key.take() {
+                guard.remove(key);
             }
+            this.registered_waker = None;
+            return Poll::Ready(());
+        }
+
+        // Register the waker only once,
+        // and replace it if the task has changed.
+        // The slot might have been emptied by `set`
+        // if another waiter has passed first in auto-reset mode.
+        let is_registered = match this.waiter_key {
+            Some(key) => guard.update(key, waker),
+            None => false,
+        };
+        if !is_registered {
+            this.waiter_key = Some(guard.insert(waker.clone()));
+        }
+        this.registered_waker = Some(waker.clone());
+        Poll::Pending
+    }
+}
 
-            Poll::Pending
+impl Drop for EventFuture {
+    /// Removes the waker so that a future dropped before completion,
+    /// such as one inside a `select!` loop, doesn't stay registered.
+    fn drop(&mut self) {
+        let Some(key) = self.waiter_key else {
+            return;
+        };
+        let mut guard = self.inner.lock();
+        let was_waiting = guard.remove(key);
+        // In auto-reset mode, this future might have been the one
+        // woken to take the flag, so pass the chance on to another waiter
+        if !was_waiting
+            && self.inner.auto_reset
+            && self.inner.flag.load(Ordering::SeqCst)
+        {
+            if let Some(waker) = guard.pop() {
+                waker.wake();
+            }
         }
     }
 }
@@ -0,0 +1,141 @@
+use crate::interface::send_rust_signal;
+use crate::panic_report::{json_string, panic_message};
+use crate::session::session_id;
+use crate::shutdown::SHUTDOWN_EVENTS;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::process::abort;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// This is a special message ID for crash reports of the Rust logic.
+pub const RUST_CRASH_ID: i32 = -3;
+
+/// Decides what happens when the `main` function of the Rust logic panics.
+/// Panics inside spawned tasks are handled by the async runtime
+/// and don't reach the `main` function.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// The Rust logic stays stopped until Dart starts it again.
+    #[default]
+    Stop,
+    /// The `main` function is run again after waiting
+    /// for a backoff that doubles with each restart.
+    /// The Rust logic stops once it has been restarted
+    /// `max_restarts` times within a session.
+    Restart {
+        max_restarts: u32,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    },
+    /// The whole process is aborted, so that the failure
+    /// is not hidden behind an app without Rust logic.
+    Abort,
+}
+
+static RESTART_POLICY: Mutex<RestartPolicy> = Mutex::new(RestartPolicy::Stop);
+
+/// Sets what happens when the `main` function of the Rust logic panics.
+/// A crash report is sent to Dart in any case.
+/// The policy is read at the time of the panic,
+/// so it can be set at the beginning of the `main` function.
+pub fn set_restart_policy(policy: RestartPolicy) {
+    let mut guard = match RESTART_POLICY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard = policy;
+}
+
+fn restart_policy() -> RestartPolicy {
+    let guard = match RESTART_POLICY.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *guard
+}
+
+/// What the supervisor does after a crash.
+enum CrashAction {
+    Stop,
+    Restart(Duration),
+    Abort,
+}
+
+impl CrashAction {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Stop => "stop",
+            Self::Restart(_) => "restart",
+            Self::Abort => "abort",
+        }
+    }
+}
+
+/// Runs the `main` function, catching its panics
+/// and restarting it according to the restart policy.
+pub fn run_supervised<F, T>(main_fn: &F)
+where
+    F: Fn() -> T,
+{
+    let session = session_id();
+    let mut restarts: u32 = 0;
+    loop {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            main_fn();
+        }));
+        let Err(payload) = result else {
+            return;
+        };
+        let action = match restart_policy() {
+            RestartPolicy::Stop => CrashAction::Stop,
+            RestartPolicy::Abort => CrashAction::Abort,
+            RestartPolicy::Restart {
+                max_restarts,
+                initial_backoff,
+                max_backoff,
+            } => {
+                if restarts < max_restarts {
+                    let factor = 2u32.saturating_pow(restarts);
+                    let backoff = initial_backoff.saturating_mul(factor);
+                    CrashAction::Restart(backoff.min(max_backoff))
+                } else {
+                    CrashAction::Stop
+                }
+            }
+        };
+        report_crash(&panic_message(payload.as_ref()), restarts, &action);
+        match action {
+            CrashAction::Stop => return,
+            CrashAction::Abort => abort(),
+            CrashAction::Restart(backoff) => {
+                // Don't restart if Dart has stopped in the meantime,
+                // as a new session will run its own Rust logic.
+                if SHUTDOWN_EVENTS.dart_stopped.wait_timeout(backoff)
+                    || session_id() != session
+                {
+                    return;
+                }
+                restarts += 1;
+            }
+        }
+    }
+}
+
+/// Sends a crash report to Dart as a JSON object.
+/// Unlike `debug_print!`, this also works in release mode.
+fn report_crash(message: &str, restarts: u32, action: &CrashAction) {
+    let backoff_millis = match action {
+        CrashAction::Restart(backoff) => backoff.as_millis(),
+        _ => 0,
+    };
+    let report = format!(
+        "{{\"message\":{},\"restarts\":{restarts},\
+        \"action\":\"{}\",\"backoffMillis\":{backoff_millis}}}",
+        json_string(message),
+        action.name(),
+    );
+    let result = send_rust_signal(RUST_CRASH_ID, Vec::new(), report.into());
+    if let Err(error) = result {
+        eprintln!("{error}\nRust logic crashed: {message}");
+    }
+}
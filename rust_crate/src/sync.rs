@@ -0,0 +1,10 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// Locks the mutex. This does not fail if the mutex
+/// is poisoned but simply ignores the failure.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
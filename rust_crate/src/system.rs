@@ -0,0 +1,91 @@
+use crate::channel::{broadcast_signal_channel, SignalReceiver, SignalSender};
+use crate::error::RinfError;
+use std::str::from_utf8;
+use std::sync::LazyLock;
+
+/// This is a special message ID for system events from Dart.
+pub const SYSTEM_EVENT_ID: i32 = -2;
+
+/// The number of recent system events kept for slow receivers.
+const SYSTEM_EVENT_CAPACITY: usize = 64;
+
+/// An event from the operating system or the Flutter engine
+/// that Dart forwards to Rust.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SystemEvent {
+    /// The app is visible and responding to user input.
+    Resumed,
+    /// The app is visible but not responding to user input.
+    Inactive,
+    /// The app is not visible to the user.
+    Hidden,
+    /// The app is not visible and may be suspended soon.
+    Paused,
+    /// The Flutter engine is running without any view.
+    Detached,
+    /// The operating system asked the app to release memory.
+    MemoryPressure,
+    /// The preferred locales of the user have changed.
+    /// Locales are given as language tags such as `en-US`,
+    /// in the order of preference.
+    LocaleChanged(Vec<String>),
+}
+
+impl SystemEvent {
+    /// Decodes the event from the raw signal sent by Dart.
+    /// The kind of event is in the message bytes,
+    /// and its payload, if any, is in the binary.
+    fn decode(message_bytes: &[u8], binary: &[u8]) -> Option<Self> {
+        let event = match from_utf8(message_bytes).ok()? {
+            "resumed" => Self::Resumed,
+            "inactive" => Self::Inactive,
+            "hidden" => Self::Hidden,
+            "paused" => Self::Paused,
+            "detached" => Self::Detached,
+            "memoryPressure" => Self::MemoryPressure,
+            "localeChanged" => {
+                let locales = from_utf8(binary)
+                    .ok()?
+                    .split(',')
+                    .filter(|locale| !locale.is_empty())
+                    .map(String::from)
+                    .collect();
+                Self::LocaleChanged(locales)
+            }
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+type SystemEventChannel =
+    LazyLock<(SignalSender<SystemEvent>, SignalReceiver<SystemEvent>)>;
+static SYSTEM_EVENT_CHANNEL: SystemEventChannel = LazyLock::new(|| {
+    let channel = broadcast_signal_channel(SYSTEM_EVENT_CAPACITY);
+    channel.0.register(SYSTEM_EVENT_ID, "SystemEvent");
+    channel
+});
+
+/// Subscribes a new receiver to system events,
+/// such as app lifecycle changes and memory pressure.
+/// Each receiver gets its own copy of every event
+/// sent after the subscription.
+pub fn system_event_receiver() -> SignalReceiver<SystemEvent> {
+    SYSTEM_EVENT_CHANNEL.1.clone()
+}
+
+/// Handles a signal from Dart that uses a reserved message ID.
+#[doc(hidden)]
+pub fn assign_system_signal(
+    message_id: i32,
+    message_bytes: &[u8],
+    binary: &[u8],
+) -> Result<(), RinfError> {
+    if message_id != SYSTEM_EVENT_ID {
+        return Err(RinfError::NoSignalHandler);
+    }
+    let system_event = SystemEvent::decode(message_bytes, binary)
+        .ok_or(RinfError::CannotDecodeMessage)?;
+    SYSTEM_EVENT_CHANNEL.0.send(system_event);
+    Ok(())
+}
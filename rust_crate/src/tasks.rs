@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A task that is currently alive.
+struct TrackedTask {
+    id: usize,
+    name: String,
+}
+
+static TRACKED_TASKS: Mutex<Vec<TrackedTask>> = Mutex::new(Vec::new());
+static LAST_TASK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Registers a long-running task by name so that it can be reported
+/// if it's still alive when the app is closing.
+/// The task is considered alive until the returned guard is dropped,
+/// so the guard should be kept inside the task.
+pub fn track_task(name: impl Into<String>) -> TaskGuard {
+    let id = LAST_TASK_ID.fetch_add(1, Ordering::Relaxed);
+    let mut guard = match TRACKED_TASKS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.push(TrackedTask {
+        id,
+        name: name.into(),
+    });
+    TaskGuard { id }
+}
+
+/// Marks a tracked task as finished when dropped.
+pub struct TaskGuard {
+    id: usize,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut guard = match TRACKED_TASKS.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.retain(|task| task.id != self.id);
+    }
+}
+
+/// Returns the names of tracked tasks that are still alive,
+/// in the order they were registered.
+pub fn alive_tasks() -> Vec<String> {
+    let guard = match TRACKED_TASKS.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    guard.iter().map(|task| task.name.clone()).collect()
+}
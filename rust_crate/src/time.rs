@@ -0,0 +1,180 @@
+use crate::sync::lock;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_family = "wasm"))]
+use std::sync::{Condvar, LazyLock};
+#[cfg(not(target_family = "wasm"))]
+use std::thread;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Returns the time elapsed since a fixed point in the past.
+/// Only the difference between two returned values is meaningful.
+#[cfg(not(target_family = "wasm"))]
+pub fn now() -> Duration {
+    static ORIGIN: LazyLock<Instant> = LazyLock::new(Instant::now);
+    ORIGIN.elapsed()
+}
+
+/// Returns the time elapsed since a fixed point in the past.
+/// Only the difference between two returned values is meaningful.
+/// `Instant` is not available on the web,
+/// so the monotonic clock of JavaScript is used instead.
+#[cfg(target_family = "wasm")]
+pub fn now() -> Duration {
+    Duration::from_secs_f64(performance_now() / 1000.0)
+}
+
+/// Creates a future that completes after the given duration.
+/// This doesn't rely on the timer of any specific async runtime.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: now() + duration,
+        timer: None,
+    }
+}
+
+/// A future that completes when its deadline has passed.
+pub struct Sleep {
+    deadline: Duration,
+    timer: Option<Arc<Mutex<TimerState>>>,
+}
+
+/// State shared between a `Sleep` future and whatever wakes it up.
+struct TimerState {
+    deadline: Duration,
+    fired: bool,
+    waker: Waker,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if now() >= this.deadline {
+            return Poll::Ready(());
+        }
+        match this.timer.as_ref() {
+            Some(timer) => {
+                let mut guard = lock(timer);
+                if guard.fired {
+                    return Poll::Ready(());
+                }
+                guard.waker.clone_from(cx.waker());
+            }
+            None => {
+                let timer = Arc::new(Mutex::new(TimerState {
+                    deadline: this.deadline,
+                    fired: false,
+                    waker: cx.waker().clone(),
+                }));
+                schedule_timer(timer.clone());
+                this.timer = Some(timer);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Timers waiting to be fired by the timer thread.
+#[cfg(not(target_family = "wasm"))]
+struct TimerQueue {
+    timers: Mutex<Vec<Arc<Mutex<TimerState>>>>,
+    condvar: Condvar, // Notifies the timer thread of new timers
+    started: AtomicBool, // Whether the timer thread is running
+}
+
+#[cfg(not(target_family = "wasm"))]
+static TIMER_QUEUE: TimerQueue = TimerQueue {
+    timers: Mutex::new(Vec::new()),
+    condvar: Condvar::new(),
+    started: AtomicBool::new(false),
+};
+
+/// Lets a dedicated thread fire the timer when its deadline has passed.
+/// The thread is started when the first timer is scheduled.
+#[cfg(not(target_family = "wasm"))]
+fn schedule_timer(timer: Arc<Mutex<TimerState>>) {
+    lock(&TIMER_QUEUE.timers).push(timer);
+    if !TIMER_QUEUE.started.swap(true, Ordering::SeqCst) {
+        thread::spawn(run_timers);
+    }
+    TIMER_QUEUE.condvar.notify_one();
+}
+
+/// Fires timers whose deadline has passed,
+/// sleeping until the nearest deadline in between.
+#[cfg(not(target_family = "wasm"))]
+fn run_timers() {
+    let mut guard = lock(&TIMER_QUEUE.timers);
+    loop {
+        let current_time = now();
+        let mut nearest: Option<Duration> = None;
+        guard.retain(|timer| {
+            // Timers that are no longer awaited can be forgotten
+            if Arc::strong_count(timer) == 1 {
+                return false;
+            }
+            let mut state = lock(timer);
+            if state.deadline <= current_time {
+                state.fired = true;
+                state.waker.wake_by_ref();
+                false
+            } else {
+                let deadline = nearest.map_or(state.deadline, |nearest| {
+                    nearest.min(state.deadline)
+                });
+                nearest = Some(deadline);
+                true
+            }
+        });
+        guard = match nearest {
+            Some(deadline) => {
+                let timeout = deadline.saturating_sub(current_time);
+                match TIMER_QUEUE.condvar.wait_timeout(guard, timeout) {
+                    Ok((inner, _)) => inner,
+                    Err(poisoned) => poisoned.into_inner().0,
+                }
+            }
+            None => match TIMER_QUEUE.condvar.wait(guard) {
+                Ok(inner) => inner,
+                Err(poisoned) => poisoned.into_inner(),
+            },
+        };
+    }
+}
+
+#[cfg(target_family = "wasm")]
+#[wasm_bindgen]
+extern "C" {
+    // Available in both the main JavaScript thread and web workers.
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &JsValue, timeout: f64) -> JsValue;
+
+    // Unlike `Date.now`, this is not affected by changes to the system clock.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// Lets the JavaScript event loop fire the timer
+/// when its deadline has passed.
+#[cfg(target_family = "wasm")]
+fn schedule_timer(timer: Arc<Mutex<TimerState>>) {
+    let timeout = lock(&timer).deadline.saturating_sub(now());
+    let handler = Closure::once_into_js(move || {
+        let mut state = lock(&timer);
+        state.fired = true;
+        state.waker.wake_by_ref();
+    });
+    set_timeout(&handler, timeout.as_secs_f64() * 1000.0);
+}
@@ -0,0 +1,347 @@
+use crate::error::RecvError;
+use crate::metrics::{
+    register_channel, ChannelCounters, ChannelStats, InspectChannel,
+};
+use crate::session::session_id;
+use crate::shutdown::{EventFuture, SHUTDOWN_EVENTS};
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+/// The `WatchSender` is used to replace the latest value of a watch channel.
+/// It is clonable, and multiple senders can update the same value.
+/// Older values are overwritten instead of being queued.
+/// The channel is closed when all senders are dropped.
+pub struct WatchSender<T> {
+    inner: Arc<SharedWatch<T>>,
+}
+
+/// The `WatchReceiver` is used to observe the latest value of a watch channel.
+/// Every receiver can read the value and wait for it to change,
+/// and new receivers are created by cloning the original.
+/// Updates that happen while a receiver is busy are coalesced,
+/// so that only the newest value is seen.
+pub struct WatchReceiver<T> {
+    inner: Arc<SharedWatch<T>>,
+    id: usize,                 // Each receiver has a unique ID
+    session: usize,            // The Dart session that this receiver belongs to
+    seen_version: AtomicUsize, // The version this receiver has observed
+}
+
+/// A reference to the latest value of a watch channel.
+/// The value is `None` if nothing has been sent yet.
+/// Senders wait while this reference is alive,
+/// so it should not be held for long.
+pub struct WatchRef<'a, T> {
+    guard: MutexGuard<'a, WatchState<T>>,
+}
+
+/// The watch state shared by senders and receivers.
+struct SharedWatch<T> {
+    state: Mutex<WatchState<T>>,
+    last_receiver_id: AtomicUsize, // The most recently assigned ID
+    senders: AtomicUsize,          // Number of senders that are alive
+    closed: AtomicBool,
+    counters: ChannelCounters,
+}
+
+/// The latest value along with the wakers of waiting receivers.
+struct WatchState<T> {
+    value: Option<T>,
+    version: usize,              // Number of values sent so far
+    wakers: Vec<(usize, Waker)>, // Wakers of waiting receivers by ID
+}
+
+impl<T> SharedWatch<T> {
+    /// Locks the watch state. This method does not fail if the mutex
+    /// is poisoned but simply ignores the failure.
+    fn lock(&self) -> MutexGuard<'_, WatchState<T>> {
+        match self.state.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Returns the counters describing the traffic of the channel.
+    /// The latest value counts as one waiting message.
+    fn stats(&self) -> ChannelStats {
+        let depth = usize::from(self.lock().value.is_some());
+        self.counters.stats(depth, 0)
+    }
+
+    /// Forgets the latest value so that receivers
+    /// of the next Dart session don't see it.
+    /// The version starts over as well, so that receivers cloned
+    /// from one that has seen nothing don't see a change.
+    /// Receivers of the previous session cannot observe changes anyway.
+    fn reset(&self) {
+        let mut guard = self.lock();
+        guard.value = None;
+        guard.version = 0;
+        for (_, waker) in guard.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Marks the channel as closed and wakes up everyone waiting on it.
+    fn close(&self) {
+        let mut guard = self.lock();
+        self.closed.store(true, Ordering::SeqCst);
+        for (_, waker) in guard.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> WatchSender<T> {
+    /// Replaces the latest value and wakes up the waiting receivers.
+    /// This method does not fail if the mutex
+    /// is poisoned but simply ignores the failure.
+    /// The value is discarded if the channel is closed.
+    pub fn send(&self, value: T) {
+        let mut guard = self.inner.lock();
+        if self.inner.closed.load(Ordering::SeqCst) {
+            return;
+        }
+        guard.value = Some(value);
+        guard.version += 1;
+        self.inner.counters.record_sent(1);
+        for (_, waker) in guard.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Returns the number of values sent so far.
+    pub fn version(&self) -> usize {
+        self.inner.lock().version
+    }
+
+    /// Closes the channel for all senders and receivers.
+    /// Values sent after this are discarded,
+    /// while receivers can still read the latest value.
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    /// Checks whether the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the counters describing the traffic of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.inner.stats()
+    }
+}
+
+impl<T: Send + 'static> WatchSender<T> {
+    /// Adds the channel to the registry
+    /// so that it appears in channel snapshots.
+    #[doc(hidden)]
+    pub fn register(&self, message_id: i32, name: &'static str) {
+        let channel: Arc<dyn InspectChannel> = self.inner.clone();
+        register_channel(message_id, name, Arc::downgrade(&channel));
+    }
+}
+
+impl<T> Clone for WatchSender<T> {
+    /// Creates another sender for the same channel.
+    fn clone(&self) -> Self {
+        self.inner.senders.fetch_add(1, Ordering::SeqCst);
+        WatchSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for WatchSender<T> {
+    /// Closes the channel if this was the last sender.
+    fn drop(&mut self) {
+        let is_last = self.inner.senders.fetch_sub(1, Ordering::SeqCst) == 1;
+        if is_last {
+            self.inner.close();
+        }
+    }
+}
+
+impl<T> WatchReceiver<T> {
+    /// Returns a reference to the latest value
+    /// without marking it as seen.
+    pub fn borrow(&self) -> WatchRef<'_, T> {
+        WatchRef {
+            guard: self.inner.lock(),
+        }
+    }
+
+    /// Returns a reference to the latest value and marks it as seen,
+    /// so that `changed` waits for the next value.
+    pub fn borrow_and_update(&self) -> WatchRef<'_, T> {
+        let guard = self.inner.lock();
+        self.seen_version.store(guard.version, Ordering::SeqCst);
+        WatchRef { guard }
+    }
+
+    /// Returns the number of values sent so far.
+    pub fn version(&self) -> usize {
+        self.inner.lock().version
+    }
+
+    /// Checks whether a value was sent
+    /// since this receiver last observed it.
+    pub fn has_changed(&self) -> bool {
+        self.inner.lock().version != self.seen_version.load(Ordering::SeqCst)
+    }
+
+    /// Waits until a value that this receiver has not seen yet is sent,
+    /// and marks it as seen. Multiple values sent in the meantime
+    /// result in a single wakeup.
+    /// If this receiver cannot observe changes anymore,
+    /// the future will return the reason as an error.
+    pub fn changed(&self) -> impl Future<Output = Result<(), RecvError>> + '_ {
+        ChangedFuture {
+            receiver: self,
+            dart_stopped: None,
+        }
+    }
+
+    /// Checks whether the channel is closed.
+    /// The latest value can still be read.
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the counters describing the traffic of the channel.
+    pub fn stats(&self) -> ChannelStats {
+        self.inner.stats()
+    }
+
+    /// Checks if a new value is available for this receiver.
+    /// If not, the task will be put to sleep until
+    /// a value is sent or the Dart session ends.
+    fn poll_changed(
+        &self,
+        dart_stopped: &mut Option<EventFuture>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), RecvError>> {
+        let mut guard = self.inner.lock();
+
+        // Receivers from a previous Dart session cannot observe changes
+        if session_id() != self.session {
+            return Poll::Ready(Err(RecvError::SessionEnded));
+        }
+        if guard.version != self.seen_version.load(Ordering::SeqCst) {
+            self.seen_version.store(guard.version, Ordering::SeqCst);
+            self.inner.counters.record_received();
+            return Poll::Ready(Ok(()));
+        }
+        if self.inner.closed.load(Ordering::SeqCst) {
+            return Poll::Ready(Err(RecvError::Closed));
+        }
+
+        // Wake up the task when Dart stops as well
+        let dart_stopped = dart_stopped
+            .get_or_insert_with(|| SHUTDOWN_EVENTS.dart_stopped.wait_async());
+        if Pin::new(dart_stopped).poll(cx).is_ready() {
+            return Poll::Ready(Err(RecvError::SessionEnded));
+        }
+        let waker = cx.waker();
+        match guard.wakers.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, existing)) => existing.clone_from(waker),
+            None => guard.wakers.push((self.id, waker.clone())),
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Clone for WatchReceiver<T> {
+    /// Creates another receiver that has seen
+    /// the same version as the original one.
+    /// Unlike signal receivers, the original receiver stays usable.
+    fn clone(&self) -> Self {
+        let id = self.inner.last_receiver_id.fetch_add(1, Ordering::SeqCst);
+        WatchReceiver {
+            inner: self.inner.clone(),
+            id: id + 1, // Increment ID for new receiver
+            session: session_id(),
+            seen_version: AtomicUsize::new(
+                self.seen_version.load(Ordering::SeqCst),
+            ),
+        }
+    }
+}
+
+impl<T> Drop for WatchReceiver<T> {
+    /// Removes the waker of this receiver
+    /// so that it doesn't occupy memory in the channel.
+    fn drop(&mut self) {
+        let mut guard = self.inner.lock();
+        guard.wakers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T: Send> InspectChannel for SharedWatch<T> {
+    fn stats(&self) -> ChannelStats {
+        SharedWatch::stats(self)
+    }
+
+    fn reset(&self) {
+        SharedWatch::reset(self);
+    }
+}
+
+impl<T> Deref for WatchRef<'_, T> {
+    type Target = Option<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard.value
+    }
+}
+
+/// A future that represents a `WatchReceiver` waiting for a new value.
+struct ChangedFuture<'a, T> {
+    receiver: &'a WatchReceiver<T>,
+    dart_stopped: Option<EventFuture>,
+}
+
+impl<T> Future for ChangedFuture<'_, T> {
+    type Output = Result<(), RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.receiver.poll_changed(&mut this.dart_stopped, cx)
+    }
+}
+
+/// Creates a watch channel with a sender and a receiver.
+/// Only the latest value is kept, and receivers are woken up
+/// once for any number of values sent while they were busy.
+#[doc(hidden)]
+pub fn watch_channel<T>() -> (WatchSender<T>, WatchReceiver<T>) {
+    let start_receiver_id = 0;
+
+    let shared = Arc::new(SharedWatch {
+        state: Mutex::new(WatchState {
+            value: None,
+            version: 0,
+            wakers: Vec::new(),
+        }),
+        last_receiver_id: AtomicUsize::new(start_receiver_id),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        counters: ChannelCounters::default(),
+    });
+
+    let sender = WatchSender {
+        inner: shared.clone(),
+    };
+    let receiver = WatchReceiver {
+        inner: shared,
+        id: start_receiver_id,
+        session: session_id(),
+        seen_version: AtomicUsize::new(0),
+    };
+    (sender, receiver)
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::block_on;
+use rinf::{signal_channel, RecvError, SignalSource};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DELAY: Duration = Duration::from_millis(100);
+const LONG_DELAY: Duration = Duration::from_secs(60);
+
+#[test]
+fn debounce_coalesces_bursts() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut debounced = receiver.debounce(DELAY);
+    let start = Instant::now();
+    for i in 0..5 {
+        sender.send(i);
+    }
+    assert_eq!(block_on(debounced.recv()), Ok(4));
+    assert!(start.elapsed() >= DELAY);
+
+    // A message sent during the delay restarts it
+    sender.send(5);
+    let start = Instant::now();
+    let later_sender = sender.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(DELAY / 2);
+        later_sender.send(6);
+    });
+    assert_eq!(block_on(debounced.recv()), Ok(6));
+    assert!(start.elapsed() >= DELAY + DELAY / 2);
+    assert!(handle.join().is_ok());
+}
+
+#[test]
+fn throttle_emits_once_per_interval() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut throttled = receiver.throttle(DELAY);
+
+    // The first message is provided immediately
+    let start = Instant::now();
+    sender.send(0);
+    assert_eq!(block_on(throttled.recv()), Ok(0));
+    assert!(start.elapsed() < DELAY);
+
+    // Only the most recent message is provided when the interval ends
+    for i in 1..5 {
+        sender.send(i);
+    }
+    assert_eq!(block_on(throttled.recv()), Ok(4));
+    assert!(start.elapsed() >= DELAY);
+
+    // Another interval has started for the messages that follow
+    sender.send(5);
+    assert_eq!(block_on(throttled.recv()), Ok(5));
+    assert!(start.elapsed() >= DELAY * 2);
+}
+
+#[test]
+fn chunks_timeout_flushes_partial_batches() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut chunks = receiver.chunks_timeout(3, DELAY);
+
+    // A full batch is provided without waiting
+    let start = Instant::now();
+    for i in 0..4 {
+        sender.send(i);
+    }
+    assert_eq!(block_on(chunks.recv()), Ok(vec![0, 1, 2]));
+    assert!(start.elapsed() < DELAY);
+
+    // A partial batch is provided when the timeout passes
+    assert_eq!(block_on(chunks.recv()), Ok(vec![3]));
+    assert!(start.elapsed() >= DELAY);
+}
+
+#[test]
+fn debounce_flushes_before_error() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut debounced = receiver.debounce(LONG_DELAY);
+    sender.send(0);
+    sender.send(1);
+    drop(sender);
+    let start = Instant::now();
+    assert_eq!(block_on(debounced.recv()), Ok(1));
+    assert_eq!(block_on(debounced.recv()), Err(RecvError::Closed));
+    assert!(start.elapsed() < LONG_DELAY);
+}
+
+#[test]
+fn throttle_flushes_before_error() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut throttled = receiver.throttle(LONG_DELAY);
+    sender.send(0);
+    assert_eq!(block_on(throttled.recv()), Ok(0));
+    sender.send(1);
+    sender.send(2);
+    drop(sender);
+    let start = Instant::now();
+    assert_eq!(block_on(throttled.recv()), Ok(2));
+    assert_eq!(block_on(throttled.recv()), Err(RecvError::Closed));
+    assert!(start.elapsed() < LONG_DELAY);
+}
+
+#[test]
+fn chunks_timeout_flushes_before_error() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let mut chunks = receiver.chunks_timeout(10, LONG_DELAY);
+    sender.send(0);
+    sender.send(1);
+    drop(sender);
+    let start = Instant::now();
+    assert_eq!(block_on(chunks.recv()), Ok(vec![0, 1]));
+    assert_eq!(block_on(chunks.recv()), Err(RecvError::Closed));
+    assert!(start.elapsed() < LONG_DELAY);
+}
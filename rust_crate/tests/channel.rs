@@ -0,0 +1,343 @@
+mod common;
+
+use common::{counting_waker, poll_once};
+use rinf::{
+    broadcast_signal_channel, signal_channel, signal_channel_with,
+    sticky_signal_channel, ChannelPolicy, RecvError,
+};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Barrier};
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MESSAGE_COUNT: usize = 10_000;
+const THREAD_COUNT: usize = 8;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn superseded_while_pending() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let (counter, waker) = counting_waker();
+    let mut pending = Box::pin(receiver.recv());
+    assert!(poll_once(&mut pending, &waker).is_pending());
+
+    // The waiting receiver is woken to notice that it was replaced
+    let new_receiver = receiver.clone();
+    assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        poll_once(&mut pending, &waker),
+        Poll::Ready(Err(RecvError::Superseded))
+    );
+
+    // Only the new receiver gets messages from now on
+    let (new_counter, new_waker) = counting_waker();
+    let mut new_pending = Box::pin(new_receiver.recv());
+    assert!(poll_once(&mut new_pending, &new_waker).is_pending());
+    sender.send(1);
+    assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+    assert_eq!(new_counter.count.load(Ordering::SeqCst), 1);
+    assert_eq!(poll_once(&mut new_pending, &new_waker), Poll::Ready(Ok(1)));
+    assert_eq!(receiver.try_recv(), Err(RecvError::Superseded));
+}
+
+#[test]
+fn close_racing_sends_keeps_sent_messages() {
+    for _ in 0..100 {
+        let (sender, receiver) = signal_channel::<usize>();
+        let barrier = Arc::new(Barrier::new(THREAD_COUNT + 1));
+        let handles: Vec<_> = (0..THREAD_COUNT)
+            .map(|_| {
+                let sender = sender.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    for i in 0..MESSAGE_COUNT / THREAD_COUNT {
+                        sender.send(i);
+                    }
+                })
+            })
+            .collect();
+        barrier.wait();
+        // Close while the senders are still busy
+        sender.close();
+        for handle in handles {
+            assert!(handle.join().is_ok());
+        }
+
+        // Messages that made it into the queue are still received,
+        // and the receiver learns that the channel is closed
+        let mut received = 0;
+        loop {
+            match receiver.recv_timeout(TIMEOUT) {
+                Ok(Some(_)) => received += 1,
+                Ok(None) => panic!("The receiver was not woken"),
+                Err(error) => {
+                    assert_eq!(error, RecvError::Closed);
+                    break;
+                }
+            }
+        }
+        assert!(received <= MESSAGE_COUNT);
+        assert_eq!(receiver.try_recv(), Err(RecvError::Closed));
+    }
+}
+
+#[test]
+fn last_sender_dropped_after_sending() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for i in 0..MESSAGE_COUNT / THREAD_COUNT {
+                    sender.send(i);
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+    let mut received = 0;
+    while let Ok(Some(_)) = receiver.recv_timeout(TIMEOUT) {
+        received += 1;
+    }
+    for handle in handles {
+        assert!(handle.join().is_ok());
+    }
+    assert_eq!(received, MESSAGE_COUNT / THREAD_COUNT * THREAD_COUNT);
+    assert_eq!(receiver.try_recv(), Err(RecvError::Closed));
+}
+
+#[test]
+fn wakeups_are_not_lost_between_register_and_recheck() {
+    // Each message is sent while the other side is going to sleep,
+    // so a message arriving between the first check
+    // and the waker registration must not be missed
+    let (ping_sender, ping_receiver) = signal_channel::<usize>();
+    let (pong_sender, pong_receiver) = signal_channel::<usize>();
+    let echo = thread::spawn(move || {
+        while let Ok(Some(msg)) = ping_receiver.recv_timeout(TIMEOUT) {
+            pong_sender.send(msg);
+        }
+    });
+    for i in 0..MESSAGE_COUNT {
+        ping_sender.send(i);
+        assert_eq!(pong_receiver.recv_timeout(TIMEOUT), Ok(Some(i)));
+    }
+    drop(ping_sender);
+    assert!(echo.join().is_ok());
+}
+
+#[test]
+fn waker_is_replaced_when_the_task_changes() {
+    let (sender, receiver) = signal_channel::<usize>();
+    let (first_counter, first_waker) = counting_waker();
+    let (second_counter, second_waker) = counting_waker();
+    let mut pending = Box::pin(receiver.recv());
+    assert!(poll_once(&mut pending, &first_waker).is_pending());
+    assert!(poll_once(&mut pending, &second_waker).is_pending());
+    sender.send(1);
+    assert_eq!(first_counter.count.load(Ordering::SeqCst), 0);
+    assert_eq!(second_counter.count.load(Ordering::SeqCst), 1);
+    assert_eq!(poll_once(&mut pending, &second_waker), Poll::Ready(Ok(1)));
+}
+
+#[test]
+fn blocked_sender_gives_up_after_a_while() {
+    let (sender, receiver) = signal_channel_with(ChannelPolicy::Block(1));
+    sender.send(1);
+    let started = Instant::now();
+    sender.send(2);
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(900));
+    assert!(elapsed < Duration::from_secs(5));
+    assert_eq!(sender.dropped_count(), 1);
+    assert_eq!(receiver.try_recv(), Ok(Some(1)));
+    assert_eq!(receiver.try_recv(), Ok(None));
+}
+
+#[test]
+fn blocked_sender_resumes_when_room_is_made() {
+    let (sender, receiver) = signal_channel_with(ChannelPolicy::Block(1));
+    sender.send(1);
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        receiver.try_recv().map(|msg| (msg, receiver))
+    });
+    sender.send(2);
+    let Ok(Ok((first, receiver))) = handle.join() else {
+        panic!("The receiver thread failed");
+    };
+    assert_eq!(first, Some(1));
+    assert_eq!(receiver.try_recv(), Ok(Some(2)));
+    assert_eq!(sender.dropped_count(), 0);
+}
+
+#[test]
+fn zero_capacity_keeps_one_message() {
+    for policy in [
+        ChannelPolicy::DropOldest(0),
+        ChannelPolicy::DropNewest(0),
+        ChannelPolicy::Block(0),
+    ] {
+        let (sender, receiver) = signal_channel_with(policy);
+        sender.send(1);
+        assert_eq!(receiver.try_recv(), Ok(Some(1)));
+        assert_eq!(receiver.try_recv(), Ok(None));
+        assert_eq!(sender.dropped_count(), 0);
+    }
+}
+
+#[test]
+fn superseded_counts_only_replaced_receivers() {
+    let (lock_free_sender, lock_free_receiver) = signal_channel::<usize>();
+    let (locked_sender, locked_receiver) =
+        signal_channel_with::<usize>(ChannelPolicy::DropOldest(4));
+
+    // Taking over from the original receiver is not counted
+    let first = lock_free_receiver.clone();
+    let locked_first = locked_receiver.clone();
+    assert_eq!(lock_free_sender.stats().superseded, 0);
+    assert_eq!(locked_sender.stats().superseded, 0);
+
+    let _second = first.clone();
+    let _locked_second = locked_first.clone();
+    assert_eq!(lock_free_sender.stats().superseded, 1);
+    assert_eq!(locked_sender.stats().superseded, 1);
+}
+
+#[test]
+fn broadcast_depth_follows_the_slowest_receiver() {
+    let (sender, receiver) = broadcast_signal_channel::<usize>(8);
+    let fast = receiver.clone();
+    let slow = receiver.clone();
+    for i in 0..5 {
+        sender.send(i);
+    }
+    assert_eq!(sender.stats().depth, 5);
+    while let Ok(Some(_)) = fast.try_recv() {}
+    assert_eq!(sender.stats().depth, 5);
+    for _ in 0..3 {
+        assert!(matches!(slow.try_recv(), Ok(Some(_))));
+    }
+    assert_eq!(sender.stats().depth, 2);
+    while let Ok(Some(_)) = slow.try_recv() {}
+    assert_eq!(sender.stats().depth, 0);
+    assert_eq!(sender.stats().high_water, 5);
+}
+
+#[test]
+fn broadcast_subscribers_get_every_message() {
+    let (sender, receiver) = broadcast_signal_channel::<usize>(8);
+    let first = receiver.clone();
+    let second = receiver.clone();
+    for i in 0..5 {
+        sender.send(i);
+    }
+    assert_eq!(first.drain(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(second.drain(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(first.try_recv(), Ok(None));
+}
+
+#[test]
+fn late_broadcast_subscriber_starts_after_the_tail() {
+    let (sender, receiver) = broadcast_signal_channel::<usize>(8);
+    let early = receiver.clone();
+    sender.send(0);
+    sender.send(1);
+    let late = receiver.clone();
+    assert_eq!(late.try_recv(), Ok(None));
+    sender.send(2);
+    assert_eq!(late.drain(), vec![2]);
+    assert_eq!(early.drain(), vec![0, 1, 2]);
+}
+
+#[test]
+fn lagging_broadcast_subscriber_skips_old_messages() {
+    let (sender, receiver) = broadcast_signal_channel::<usize>(3);
+    let subscriber = receiver.clone();
+    for i in 0..5 {
+        sender.send(i);
+    }
+    assert_eq!(subscriber.lagged_count(), 0);
+    assert_eq!(subscriber.drain(), vec![2, 3, 4]);
+    assert_eq!(subscriber.lagged_count(), 2);
+
+    // Keeping up doesn't add to the lag
+    sender.send(5);
+    assert_eq!(subscriber.drain(), vec![5]);
+    assert_eq!(subscriber.lagged_count(), 2);
+}
+
+#[test]
+fn dropped_broadcast_subscriber_stops_counting() {
+    let (sender, receiver) = broadcast_signal_channel::<usize>(8);
+    let active = receiver.clone();
+    let idle = receiver.clone();
+    for i in 0..4 {
+        sender.send(i);
+    }
+    assert_eq!(active.drain().len(), 4);
+    assert_eq!(sender.stats().depth, 4);
+
+    // The idle receiver's position is forgotten along with it
+    drop(idle);
+    assert_eq!(sender.stats().depth, 0);
+    sender.send(4);
+    assert_eq!(sender.stats().depth, 1);
+    assert_eq!(active.drain(), vec![4]);
+}
+
+#[test]
+fn sticky_receiver_gets_the_latest_message_again() {
+    let (sender, receiver) =
+        sticky_signal_channel::<usize>(ChannelPolicy::Unbounded);
+    let first = receiver.clone();
+    sender.send(0);
+    sender.send(1);
+    assert_eq!(first.drain(), vec![0, 1]);
+
+    // A new receiver starts from the message that was already received
+    let second = receiver.clone();
+    assert_eq!(second.try_recv(), Ok(Some(1)));
+    assert_eq!(second.try_recv(), Ok(None));
+    sender.send(2);
+    assert_eq!(second.drain(), vec![2]);
+    assert_eq!(sender.dropped_count(), 0);
+}
+
+#[test]
+fn sticky_receiver_skips_the_backlog() {
+    let (sender, receiver) =
+        sticky_signal_channel::<usize>(ChannelPolicy::Unbounded);
+    for i in 0..4 {
+        sender.send(i);
+    }
+
+    // Messages older than the latest one are counted as dropped
+    let fresh = receiver.clone();
+    assert_eq!(fresh.drain(), vec![3]);
+    assert_eq!(sender.dropped_count(), 3);
+    assert_eq!(fresh.dropped_count(), 3);
+}
+
+#[test]
+fn sticky_replay_keeps_the_original_lifetime() {
+    let lifetime = Duration::from_millis(50);
+    let (sender, receiver) =
+        sticky_signal_channel::<usize>(ChannelPolicy::Expire(lifetime));
+    sender.send(0);
+    let first = receiver.clone();
+    assert_eq!(first.try_recv(), Ok(Some(0)));
+
+    // A replayed message is not given a new lifetime
+    thread::sleep(lifetime * 2);
+    let second = receiver.clone();
+    assert_eq!(second.try_recv(), Ok(None));
+    assert_eq!(sender.dropped_count(), 1);
+
+    // A message that is still alive is replayed
+    sender.send(1);
+    let third = receiver.clone();
+    assert_eq!(third.try_recv(), Ok(Some(1)));
+}
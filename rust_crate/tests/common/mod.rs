@@ -0,0 +1,78 @@
+//! Helpers for driving futures by hand, shared by the integration tests.
+//! Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+
+/// A waker that counts how many times it was woken.
+pub struct CountingWaker {
+    pub count: AtomicUsize,
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+pub fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+    let counter = Arc::new(CountingWaker {
+        count: AtomicUsize::new(0),
+    });
+    let waker = Waker::from(counter.clone());
+    (counter, waker)
+}
+
+/// Polls the future once with the given waker.
+pub fn poll_once<F: Future + Unpin>(
+    future: &mut F,
+    waker: &Waker,
+) -> Poll<F::Output> {
+    let mut cx = Context::from_waker(waker);
+    std::pin::Pin::new(future).poll(&mut cx)
+}
+
+/// A waker that does nothing.
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Polls the future once without caring about wakeups.
+pub fn poll_now<F: Future>(future: F) -> Poll<F::Output> {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    pin!(future).poll(&mut cx)
+}
+
+/// A waker that unparks the thread which is blocked on a future.
+struct ThreadWaker {
+    thread: thread::Thread,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.thread.unpark();
+    }
+}
+
+/// Runs the future to completion on the current thread.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker {
+        thread: thread::current(),
+    }));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
@@ -0,0 +1,363 @@
+mod common;
+
+use common::{block_on, counting_waker, poll_now, poll_once};
+use rinf::{CancellationToken, Event};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WAITER_COUNT: usize = 10_000;
+const THREAD_COUNT: usize = 8;
+
+#[test]
+fn set_wakes_every_waiter() {
+    let event = Event::new();
+    let (counter, waker) = counting_waker();
+    let mut futures: Vec<_> =
+        (0..WAITER_COUNT).map(|_| event.wait_async()).collect();
+    for future in futures.iter_mut() {
+        assert!(poll_once(future, &waker).is_pending());
+    }
+    assert_eq!(event.waiter_count(), WAITER_COUNT);
+
+    event.set();
+    assert_eq!(counter.count.load(Ordering::SeqCst), WAITER_COUNT);
+    assert_eq!(event.waiter_count(), 0);
+    for future in futures.iter_mut() {
+        assert!(poll_once(future, &waker).is_ready());
+    }
+}
+
+#[test]
+fn dropped_waiters_are_unregistered() {
+    let event = Event::new();
+    let (_, waker) = counting_waker();
+    let mut futures: Vec<_> =
+        (0..WAITER_COUNT).map(|_| event.wait_async()).collect();
+    for future in futures.iter_mut() {
+        assert!(poll_once(future, &waker).is_pending());
+    }
+    futures.truncate(WAITER_COUNT / 2);
+    assert_eq!(event.waiter_count(), WAITER_COUNT / 2);
+
+    // Futures dropped from a `select!` loop shouldn't pile up
+    for _ in 0..WAITER_COUNT * 10 {
+        let mut future = event.wait_async();
+        assert!(poll_once(&mut future, &waker).is_pending());
+    }
+    assert_eq!(event.waiter_count(), WAITER_COUNT / 2);
+
+    drop(futures);
+    assert_eq!(event.waiter_count(), 0);
+}
+
+#[test]
+fn repolling_replaces_the_waker() {
+    let event = Event::new();
+    let mut future = event.wait_async();
+    let mut counters = Vec::new();
+    for _ in 0..1_000 {
+        let (counter, waker) = counting_waker();
+        assert!(poll_once(&mut future, &waker).is_pending());
+        counters.push(counter);
+    }
+    assert_eq!(event.waiter_count(), 1);
+
+    event.set();
+    let woken: usize = counters
+        .iter()
+        .map(|counter| counter.count.load(Ordering::SeqCst))
+        .sum();
+    assert_eq!(woken, 1);
+    assert!(counters
+        .last()
+        .is_some_and(|counter| counter.count.load(Ordering::SeqCst) == 1));
+}
+
+#[test]
+fn stale_future_keeps_new_waiter() {
+    let event = Event::new();
+    let (_, waker) = counting_waker();
+    let mut stale = event.wait_async();
+    assert!(poll_once(&mut stale, &waker).is_pending());
+    event.set();
+    event.clear();
+
+    // The new future may reuse the slot of the stale one
+    let (counter, waker) = counting_waker();
+    let mut fresh = event.wait_async();
+    assert!(poll_once(&mut fresh, &waker).is_pending());
+    drop(stale);
+    assert_eq!(event.waiter_count(), 1);
+
+    event.set();
+    assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+    assert!(poll_once(&mut fresh, &waker).is_ready());
+}
+
+#[test]
+fn concurrent_waiters_are_woken() {
+    let event = Arc::new(Event::new());
+    let barrier = Arc::new(Barrier::new(THREAD_COUNT + 1));
+    let per_thread = WAITER_COUNT / THREAD_COUNT;
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| {
+            let event = event.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let (counter, waker) = counting_waker();
+                let mut futures: Vec<_> =
+                    (0..per_thread).map(|_| event.wait_async()).collect();
+                for future in futures.iter_mut() {
+                    assert!(poll_once(future, &waker).is_pending());
+                }
+                // Drop half of them before the event is set
+                futures.truncate(per_thread / 2);
+                barrier.wait(); // Registered
+                barrier.wait(); // Set
+                assert_eq!(
+                    counter.count.load(Ordering::SeqCst),
+                    per_thread / 2
+                );
+                for future in futures.iter_mut() {
+                    assert!(poll_once(future, &waker).is_ready());
+                }
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    assert_eq!(event.waiter_count(), THREAD_COUNT * (per_thread / 2));
+    event.set();
+    barrier.wait();
+    for handle in handles {
+        assert!(handle.join().is_ok());
+    }
+    assert_eq!(event.waiter_count(), 0);
+}
+
+#[test]
+fn waiters_survive_churn_while_setting() {
+    let event = Arc::new(Event::new());
+    let running = Arc::new(AtomicBool::new(true));
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| {
+            let event = event.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                let (_, waker) = counting_waker();
+                while running.load(Ordering::SeqCst) {
+                    let mut futures: Vec<_> =
+                        (0..64).map(|_| event.wait_async()).collect();
+                    for future in futures.iter_mut() {
+                        let _ = poll_once(future, &waker);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for _ in 0..1_000 {
+        event.set();
+        event.clear();
+    }
+    running.store(false, Ordering::SeqCst);
+    for handle in handles {
+        assert!(handle.join().is_ok());
+    }
+    assert_eq!(event.waiter_count(), 0);
+}
+
+#[test]
+fn blocking_waiters_are_released() {
+    let event = Arc::new(Event::new());
+    let barrier = Arc::new(Barrier::new(THREAD_COUNT * 8 + 1));
+    let handles: Vec<_> = (0..THREAD_COUNT * 8)
+        .map(|_| {
+            let event = event.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                event.wait();
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    event.set();
+    for handle in handles {
+        assert!(handle.join().is_ok());
+    }
+}
+
+#[test]
+fn timeouts_expire_without_set() {
+    let event = Event::new();
+    let started = Instant::now();
+    assert!(!event.wait_timeout(Duration::from_millis(50)));
+    assert!(!block_on(
+        event.wait_async_timeout(Duration::from_millis(50))
+    ));
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert_eq!(event.waiter_count(), 0);
+
+    event.set();
+    assert!(event.wait_timeout(Duration::from_secs(5)));
+    assert!(block_on(event.wait_async_timeout(Duration::from_secs(5))));
+}
+
+#[test]
+fn auto_reset_releases_one_waiter() {
+    let event = Event::new_auto_reset();
+    let (_, waker) = counting_waker();
+    let mut futures: Vec<_> = (0..3).map(|_| event.wait_async()).collect();
+    for future in futures.iter_mut() {
+        assert!(poll_once(future, &waker).is_pending());
+    }
+
+    for remaining in (0..3).rev() {
+        event.set();
+        futures.retain_mut(|future| poll_once(future, &waker).is_pending());
+        assert_eq!(futures.len(), remaining);
+        assert!(!event.is_set());
+    }
+}
+
+#[test]
+fn auto_reset_keeps_flag_until_taken() {
+    let event = Event::new_auto_reset();
+    event.set();
+    assert!(event.is_set());
+    assert!(event.wait_timeout(Duration::from_secs(5)));
+    assert!(!event.is_set());
+    assert!(!event.wait_timeout(Duration::from_millis(10)));
+}
+
+#[test]
+fn auto_reset_passes_on_from_dropped_future() {
+    let event = Event::new_auto_reset();
+    let (first_counter, first_waker) = counting_waker();
+    let (second_counter, second_waker) = counting_waker();
+    let mut first = event.wait_async();
+    let mut second = event.wait_async();
+    assert!(poll_once(&mut first, &first_waker).is_pending());
+    assert!(poll_once(&mut second, &second_waker).is_pending());
+
+    // The woken future is dropped before taking the flag
+    event.set();
+    assert_eq!(first_counter.count.load(Ordering::SeqCst), 1);
+    drop(first);
+    assert_eq!(second_counter.count.load(Ordering::SeqCst), 1);
+    assert!(poll_once(&mut second, &second_waker).is_ready());
+    assert!(!event.is_set());
+}
+
+#[test]
+fn auto_reset_releases_blocked_threads_one_by_one() {
+    let event = Arc::new(Event::new_auto_reset());
+    let released = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| {
+            let event = event.clone();
+            let released = released.clone();
+            thread::spawn(move || {
+                event.wait();
+                released.fetch_add(1, Ordering::SeqCst);
+            })
+        })
+        .collect();
+
+    for count in 1..=THREAD_COUNT {
+        event.set();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while released.load(Ordering::SeqCst) < count {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(released.load(Ordering::SeqCst), count);
+    }
+    for handle in handles {
+        assert!(handle.join().is_ok());
+    }
+}
+
+#[test]
+fn set_count_tracks_every_set() {
+    let event = Event::new();
+    assert_eq!(event.set_count(), 0);
+    event.set();
+    event.clear();
+    event.set();
+    assert_eq!(event.set_count(), 2);
+}
+
+#[test]
+fn cancelling_a_token_cancels_its_children() {
+    let parent = CancellationToken::new();
+    let child = parent.child_token();
+    let grandchild = child.child_token();
+    let sibling = parent.child_token();
+
+    // Cancelling a child leaves its parent and siblings untouched
+    child.cancel();
+    assert!(child.is_cancelled());
+    assert!(grandchild.is_cancelled());
+    assert!(!parent.is_cancelled());
+    assert!(!sibling.is_cancelled());
+
+    parent.cancel();
+    assert!(sibling.is_cancelled());
+
+    // A child of a cancelled token starts out cancelled
+    assert!(parent.child_token().is_cancelled());
+}
+
+#[test]
+fn cancelling_a_token_wakes_its_waiters() {
+    let parent = CancellationToken::new();
+    let child = parent.child_token();
+    let (counter, waker) = counting_waker();
+    let mut parent_future = Box::pin(parent.cancelled());
+    let mut child_future = Box::pin(child.cancelled());
+    assert!(poll_once(&mut parent_future, &waker).is_pending());
+    assert!(poll_once(&mut child_future, &waker).is_pending());
+
+    parent.cancel();
+    assert_eq!(counter.count.load(Ordering::SeqCst), 2);
+    assert!(poll_once(&mut parent_future, &waker).is_ready());
+    assert!(poll_once(&mut child_future, &waker).is_ready());
+}
+
+#[test]
+fn dropped_cancellation_waiters_are_not_woken() {
+    let token = CancellationToken::new();
+    let (counter, waker) = counting_waker();
+    for _ in 0..WAITER_COUNT {
+        let mut future = Box::pin(token.cancelled());
+        assert!(poll_once(&mut future, &waker).is_pending());
+    }
+    token.cancel();
+    assert_eq!(counter.count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn run_until_cancelled_stops_the_future() {
+    let token = CancellationToken::new();
+    assert_eq!(
+        poll_now(token.run_until_cancelled(async { 1 })),
+        Poll::Ready(Some(1))
+    );
+
+    // A pending future is abandoned once the token is cancelled
+    let event = Event::new();
+    let (counter, waker) = counting_waker();
+    let mut future = Box::pin(token.run_until_cancelled(event.wait_async()));
+    assert!(poll_once(&mut future, &waker).is_pending());
+    token.cancel();
+    assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+    assert_eq!(poll_once(&mut future, &waker), Poll::Ready(None));
+    drop(future);
+    assert_eq!(event.waiter_count(), 0);
+}
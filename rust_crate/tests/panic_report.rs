@@ -0,0 +1,42 @@
+//! These checks share the global panic hook,
+//! so they run one after another in a single test.
+
+use rinf::{set_crash_directory, start_rust_logic};
+use std::fs;
+use std::panic::{catch_unwind, set_hook};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How many times the hook installed before Rinf's was called.
+static PREVIOUS_HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn crash_files_are_unique_for_each_panic() {
+    let directory =
+        std::env::temp_dir().join(format!("rinf-crash-{}", process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    assert!(fs::create_dir_all(&directory).is_ok());
+
+    // Install the panic hook of Rinf over an existing one,
+    // starting twice as a hot restart would
+    set_hook(Box::new(|_| {
+        PREVIOUS_HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+    }));
+    let _ = start_rust_logic(|| {});
+    let _ = start_rust_logic(|| {});
+    set_crash_directory(Some(directory.clone()));
+    for _ in 0..10 {
+        assert!(catch_unwind(|| panic!("Expected panic")).is_err());
+    }
+    set_crash_directory(None);
+
+    // The existing hook is still called,
+    // and each panic is reported only once
+    assert_eq!(PREVIOUS_HOOK_CALLS.load(Ordering::SeqCst), 10);
+    let file_count = match fs::read_dir(&directory) {
+        Ok(entries) => entries.count(),
+        Err(error) => panic!("{error}"),
+    };
+    let _ = fs::remove_dir_all(&directory);
+    assert_eq!(file_count, 10);
+}
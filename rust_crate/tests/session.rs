@@ -0,0 +1,153 @@
+//! These checks share the global Dart session,
+//! so they run one after another in a single test.
+
+mod common;
+
+use common::poll_now;
+use rinf::{
+    channel_snapshot, dart_shutdown, on_shutdown, register_reset, session_info,
+    shutdown_token, signal_channel, start_rust_logic, sticky_signal_channel,
+    watch_channel, ChannelPolicy, Event, RecvError, StartKind,
+    MERGED_SIGNAL_ID,
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+
+extern "C" {
+    fn stop_rust_logic_extern();
+}
+
+static RECEIVING: AtomicBool = AtomicBool::new(false);
+
+fn reset_receiving() {
+    RECEIVING.store(false, Ordering::SeqCst);
+}
+
+#[test]
+fn sessions_end_after_queued_messages() {
+    assert_eq!(session_info().id, 0);
+    assert_eq!(session_info().start_kind, StartKind::NotStarted);
+    let _ = start_rust_logic(|| {});
+    assert_eq!(session_info().start_kind, StartKind::ColdStart);
+
+    // Queued messages are still received after Dart stops
+    let (sender, receiver) = signal_channel();
+    sender.send(1);
+    sender.send(2);
+    let root_token = shutdown_token();
+    let child_token = root_token.child_token();
+    let mut child_cancelled = Box::pin(child_token.cancelled());
+    assert!(poll_now(child_cancelled.as_mut()).is_pending());
+    unsafe { stop_rust_logic_extern() };
+    assert_eq!(receiver.try_recv(), Ok(Some(1)));
+    assert_eq!(poll_now(receiver.recv()), Poll::Ready(Ok(2)));
+    assert_eq!(receiver.try_recv(), Err(RecvError::SessionEnded));
+    assert_eq!(
+        poll_now(receiver.recv()),
+        Poll::Ready(Err(RecvError::SessionEnded))
+    );
+
+    // The root token and its children are cancelled when Dart stops
+    assert!(root_token.is_cancelled());
+    assert!(child_token.is_cancelled());
+    assert!(poll_now(child_cancelled.as_mut()).is_ready());
+
+    // Receivers of a previous session don't get anything after a restart
+    sender.send(3);
+    let _ = start_rust_logic(|| {});
+    assert_eq!(session_info().start_kind, StartKind::Restart);
+    assert_eq!(receiver.try_recv(), Err(RecvError::SessionEnded));
+    let new_receiver = receiver.clone();
+    assert_eq!(new_receiver.try_recv(), Ok(Some(3)));
+    assert_eq!(new_receiver.try_recv(), Ok(None));
+
+    // A new session gets a fresh root token
+    let new_token = shutdown_token();
+    assert!(!new_token.is_cancelled());
+    assert!(!new_token.child_token().is_cancelled());
+    assert!(root_token.is_cancelled());
+
+    // Registered channels and generated state are reset on a restart
+    let (merged_sender, merged_receiver) = signal_channel();
+    merged_sender.register(MERGED_SIGNAL_ID, "MergedDartSignal");
+    register_reset(reset_receiving);
+    RECEIVING.store(true, Ordering::SeqCst);
+    merged_sender.send(4);
+    assert!(channel_snapshot()
+        .iter()
+        .any(|snapshot| snapshot.name == "MergedDartSignal"
+            && snapshot.message_id == MERGED_SIGNAL_ID));
+    let _ = start_rust_logic(|| {});
+    assert!(!RECEIVING.load(Ordering::SeqCst));
+    assert_eq!(merged_receiver.clone().try_recv(), Ok(None));
+
+    // Watch receivers of a new session start from a clean state
+    let (watch_sender, watch_receiver) = watch_channel();
+    watch_sender.register(1001, "WatchedValue");
+    watch_sender.send(1);
+    let _ = start_rust_logic(|| {});
+    let fresh_receiver = watch_receiver.clone();
+    assert!(poll_now(fresh_receiver.changed()).is_pending());
+    assert_eq!(*fresh_receiver.borrow(), None);
+    watch_sender.send(2);
+    assert_eq!(poll_now(fresh_receiver.changed()), Poll::Ready(Ok(())));
+    assert_eq!(*fresh_receiver.borrow(), Some(2));
+
+    // Sticky receivers of a new session don't replay an old message
+    let (sticky_sender, sticky_receiver) =
+        sticky_signal_channel(ChannelPolicy::Unbounded);
+    sticky_sender.register(1002, "StickyValue");
+    sticky_sender.send(1);
+    assert_eq!(sticky_receiver.clone().try_recv(), Ok(Some(1)));
+    let _ = start_rust_logic(|| {});
+    assert_eq!(sticky_receiver.clone().try_recv(), Ok(None));
+    sticky_sender.send(2);
+    assert_eq!(sticky_receiver.clone().try_recv(), Ok(Some(2)));
+
+    // Every awaiter of `dart_shutdown` waits until the hooks have finished
+    let gate = Arc::new(Event::new());
+    let hook_runs = Arc::new(AtomicUsize::new(0));
+    let hook_gate = gate.clone();
+    let hook_counter = hook_runs.clone();
+    on_shutdown(0, move || async move {
+        hook_gate.wait_async().await;
+        hook_counter.fetch_add(1, Ordering::SeqCst);
+        Ok::<(), String>(())
+    });
+    let mut first = Box::pin(dart_shutdown());
+    let mut second = Box::pin(dart_shutdown());
+    unsafe { stop_rust_logic_extern() };
+    assert!(poll_now(first.as_mut()).is_pending());
+    assert!(poll_now(second.as_mut()).is_pending());
+    gate.set();
+    assert!(poll_now(second.as_mut()).is_pending());
+    assert!(poll_now(first.as_mut()).is_ready());
+    assert!(poll_now(second.as_mut()).is_ready());
+    assert_eq!(hook_runs.load(Ordering::SeqCst), 1);
+
+    // Hooks of a new session are not taken by the previous one
+    let old_hook_ran = Arc::new(AtomicBool::new(false));
+    let new_hook_ran = Arc::new(AtomicBool::new(false));
+    let _ = start_rust_logic(|| {});
+    let old_flag = old_hook_ran.clone();
+    on_shutdown(0, move || async move {
+        old_flag.store(true, Ordering::SeqCst);
+        Ok::<(), String>(())
+    });
+    let mut old_shutdown = Box::pin(dart_shutdown());
+    let _ = start_rust_logic(|| {});
+    let new_flag = new_hook_ran.clone();
+    on_shutdown(0, move || async move {
+        new_flag.store(true, Ordering::SeqCst);
+        Ok::<(), String>(())
+    });
+    let mut new_shutdown = Box::pin(dart_shutdown());
+    assert!(poll_now(old_shutdown.as_mut()).is_ready());
+    assert!(old_hook_ran.load(Ordering::SeqCst));
+    assert!(!new_hook_ran.load(Ordering::SeqCst));
+    assert!(poll_now(new_shutdown.as_mut()).is_pending());
+    unsafe { stop_rust_logic_extern() };
+    assert!(poll_now(new_shutdown.as_mut()).is_ready());
+    assert!(new_hook_ran.load(Ordering::SeqCst));
+}
@@ -0,0 +1,69 @@
+//! These checks share the global Dart connection,
+//! so they run one after another in a single test.
+
+use rinf::{
+    dart_connected, delivery_failures, dropped_signals, send_rust_signal,
+    set_signal_buffer, start_rust_logic, BufferOverflow, RinfError,
+};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+extern "C" {
+    fn prepare_isolate_extern(
+        store_post_object: unsafe extern "C" fn(i64, *mut c_void) -> bool,
+        port: i64,
+    );
+}
+
+/// Whether the fake Dart port accepts signals.
+static ACCEPTING: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" fn post_object(_port: i64, _message: *mut c_void) -> bool {
+    ACCEPTING.load(Ordering::SeqCst)
+}
+
+fn send() -> Result<(), RinfError> {
+    send_rust_signal(1, Vec::new(), Vec::new())
+}
+
+#[test]
+fn signals_are_buffered_only_after_dart_starts_rust() {
+    // Without Dart, reports can fall back to the console
+    assert!(matches!(send(), Err(RinfError::NoDartIsolate)));
+
+    // Once started, signals wait for Dart to bind its isolate
+    let _ = start_rust_logic(|| {});
+    assert!(send().is_ok());
+
+    // Signals discarded from a full buffer are counted
+    set_signal_buffer(2, BufferOverflow::DropOldest);
+    assert!(send().is_ok());
+    assert_eq!(dropped_signals(), 0);
+    assert!(send().is_ok());
+    assert_eq!(dropped_signals(), 1);
+    set_signal_buffer(2, BufferOverflow::DropNewest);
+    assert!(matches!(send(), Err(RinfError::NoDartIsolate)));
+    assert_eq!(dropped_signals(), 2);
+
+    // Buffered signals are flushed to the isolate, which rejects them,
+    // and it is considered dead after repeated failures
+    unsafe { prepare_isolate_extern(post_object, 0) };
+    assert_eq!(delivery_failures(), 2);
+    assert!(dart_connected());
+    assert!(matches!(send(), Err(RinfError::DeliveryFailed)));
+    assert!(!dart_connected());
+
+    // A dead isolate makes signals fail instead of filling the buffer
+    for _ in 0..5 {
+        assert!(matches!(send(), Err(RinfError::DeliveryFailed)));
+    }
+    assert_eq!(delivery_failures(), 8);
+    assert_eq!(dropped_signals(), 2);
+
+    // Binding a new isolate delivers signals again
+    ACCEPTING.store(true, Ordering::SeqCst);
+    unsafe { prepare_isolate_extern(post_object, 0) };
+    assert!(dart_connected());
+    assert!(send().is_ok());
+    assert_eq!(delivery_failures(), 8);
+}
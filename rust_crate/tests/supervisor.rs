@@ -0,0 +1,60 @@
+//! These checks share the global Dart session,
+//! so they run one after another in a single test.
+
+use rinf::{set_restart_policy, start_rust_logic, RestartPolicy};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+extern "C" {
+    fn stop_rust_logic_extern();
+}
+
+/// How many times the `main` function has been run.
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+fn crashing_main() {
+    RUNS.fetch_add(1, Ordering::SeqCst);
+    panic!("Expected panic");
+}
+
+/// Waits until the `main` function has been run the given number of times.
+fn wait_for_runs(count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while RUNS.load(Ordering::SeqCst) < count {
+        assert!(Instant::now() < deadline, "Rust logic wasn't restarted");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn crashed_rust_logic_is_restarted_within_limits() {
+    // The backoff doubles up to its maximum, 10 + 20 + 20 milliseconds
+    set_restart_policy(RestartPolicy::Restart {
+        max_restarts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+    });
+    let start = Instant::now();
+    let _ = start_rust_logic(crashing_main);
+    wait_for_runs(4);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    // The Rust logic stays stopped after the last restart
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(RUNS.load(Ordering::SeqCst), 4);
+
+    // Dart stopping during the backoff ends the restarts
+    let backoff = Duration::from_millis(300);
+    set_restart_policy(RestartPolicy::Restart {
+        max_restarts: 3,
+        initial_backoff: backoff,
+        max_backoff: backoff,
+    });
+    RUNS.store(0, Ordering::SeqCst);
+    let _ = start_rust_logic(crashing_main);
+    wait_for_runs(1);
+    unsafe { stop_rust_logic_extern() };
+    thread::sleep(backoff * 2);
+    assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+}